@@ -1,10 +1,14 @@
-use std::ops::{Add, Sub};
+use std::{
+    iter::Sum,
+    ops::{Add, Sub},
+};
 
 use malachite::{Natural, base::num::arithmetic::traits::SaturatingSub};
 use rapidhash::RapidHashMap;
 
 use crate::{
     AnyBox, BoxKind, BoxType, BoxValue, BoxVariant, Color, MultinumBox, NumBox, PolynumBox,
+    mul::{OverflowError, TypeMismatchError},
 };
 
 /// Trait for the output type of box addition
@@ -64,82 +68,129 @@ impl BoxKind {
     }
 }
 
-impl<T: BoxType> BoxValue<T> {
-    fn add_child_boxes(self, unique_children: &mut RapidHashMap<u64, BoxValue<AnyBox>>) {
-        for child in self {
-            let child_col = child.get_color(0);
-            let child_mul = child.get_multiplicity(0);
-
-            let hash = child.hash_content(unique_children.hasher());
-
-            if let Some(other) = unique_children.get_mut(&hash)
-                && child.is_eq_content(other)
-            {
-                let other_col = other.get_color(0);
-                let other_mul = other.get_multiplicity(0);
-
-                if child_col + other_col == Color::Red {
-                    if child_mul < other_mul {
-                        other.set_multiplicity(0, other_mul.saturating_sub(child_mul));
-                    } else {
-                        other.set_multiplicity(0, child_mul.saturating_sub(other_mul));
-                        other.set_color(0, child_col);
-                    }
-                } else {
-                    other.set_multiplicity(0, other_mul + child_mul);
-                }
+/// Merge a child box into the unique children of a sum
+///
+/// Equal boxes add their multiplicities, while a box and an equal anti-box cancel out.
+pub(crate) fn merge_child(
+    unique_children: &mut RapidHashMap<u64, BoxValue<AnyBox>>,
+    child: BoxValue<AnyBox>,
+) {
+    let child_col = child.get_color(0);
+    let child_mul = child.get_multiplicity(0);
+
+    let hash = child.hash_content(unique_children.hasher());
+
+    if let Some(other) = unique_children.get_mut(&hash)
+        && child.is_eq_content(other)
+    {
+        let other_col = other.get_color(0);
+        let other_mul = other.get_multiplicity(0);
+
+        if child_col + other_col == Color::Red {
+            if child_mul < other_mul {
+                other.set_multiplicity(0, other_mul.saturating_sub(child_mul));
             } else {
-                unique_children.insert(hash, child);
+                other.set_multiplicity(0, child_mul.saturating_sub(other_mul));
+                other.set_color(0, child_col);
             }
+        } else {
+            other.set_multiplicity(0, other_mul + child_mul);
         }
+    } else {
+        unique_children.insert(hash, child);
     }
 }
 
-impl<L: BoxType + BoxAdd<R>, R: BoxType> Add<BoxValue<R>> for BoxValue<L> {
-    type Output = BoxValue<L::Output>;
-
-    fn add(self, rhs: BoxValue<R>) -> Self::Output {
-        let lhs_col = self.get_color(0);
-        let rhs_col = rhs.get_color(0);
-
-        let lhs_kind = self.get_kind(0);
-        let rhs_kind = rhs.get_kind(0);
+impl<T: BoxType> BoxValue<T> {
+    fn add_child_boxes(
+        self,
+        unique_children: &mut RapidHashMap<u64, BoxValue<AnyBox>>,
+        invert: bool,
+    ) {
+        for child in self {
+            let child = if invert { child.into_anti() } else { child };
+            merge_child(unique_children, child);
+        }
+    }
+}
 
-        let mut result = BoxValue::<L::Output>::new();
-        result.kinds.push(BoxKind::Any);
-        result.colors.push(lhs_col + rhs_col);
-        result.multiplicities.push(Natural::from(1_u32));
-        result.lengths.push(1);
+/// Add two boxes by merging their child boxes
+///
+/// If `cancel` is set, the sum of two boxes of the same color keeps that color. If exactly one
+/// of them is an anti-box, the child boxes of the right box are inverted, so that they cancel
+/// against the child boxes of the left box, and the sign of the result is moved into its
+/// children, so the result does not depend on the order of the operands. Otherwise the colors
+/// of the two boxes are combined and the child boxes are merged as they are.
+pub(crate) fn add_boxes<L: BoxType + BoxAdd<R>, R: BoxType>(
+    lhs: BoxValue<L>,
+    rhs: BoxValue<R>,
+    cancel: bool,
+) -> BoxValue<L::Output> {
+    let lhs_col = lhs.get_color(0);
+    let rhs_col = rhs.get_color(0);
+
+    let lhs_kind = lhs.get_kind(0);
+    let rhs_kind = rhs.get_kind(0);
+
+    let invert = cancel && lhs_col != rhs_col;
+
+    let mut result = BoxValue::<L::Output>::new();
+    result.kinds.push(BoxKind::Any);
+    result
+        .colors
+        .push(if cancel { lhs_col } else { lhs_col + rhs_col });
+    result.multiplicities.push(Natural::from(1_u32));
+    result.lengths.push(1);
+
+    let mut unique_children: RapidHashMap<u64, BoxValue<AnyBox>> = RapidHashMap::default();
+    lhs.add_child_boxes(&mut unique_children, false);
+    rhs.add_child_boxes(&mut unique_children, invert);
+
+    let mut max_depth: u32 = 0;
+    for child in unique_children.into_values() {
+        let mult = child.get_multiplicity(0);
+        if mult == 0 {
+            continue;
+        }
 
-        let mut unique_children: RapidHashMap<u64, BoxValue<AnyBox>> = RapidHashMap::default();
-        self.add_child_boxes(&mut unique_children);
-        rhs.add_child_boxes(&mut unique_children);
+        let child_len = child.get_length(0);
+        max_depth = max_depth.max(child_len);
+        result.extend(child);
+    }
 
-        let mut max_depth: u32 = 0;
-        for child in unique_children.into_values() {
-            let mult = child.get_multiplicity(0);
-            if mult == 0 {
-                continue;
-            }
+    let kind = L::Output::KIND;
+    let new_kind = if kind == BoxKind::Num || kind == BoxKind::Polynum || kind == BoxKind::Multinum
+    {
+        BoxKind::get_kind_from_depth(max_depth)
+    } else {
+        lhs_kind + rhs_kind
+    };
 
-            let child_len = child.get_length(0);
-            max_depth = max_depth.max(child_len);
-            result.extend(child);
-        }
+    // set kind of box based on final result
+    result.kinds[0] = new_kind;
 
-        let kind = L::Output::KIND;
-        let new_kind =
-            if kind == BoxKind::Num || kind == BoxKind::Polynum || kind == BoxKind::Multinum {
-                BoxKind::get_kind_from_depth(max_depth)
-            } else {
-                lhs_kind + rhs_kind
-            };
+    result.sort_immediate_children();
+    if invert {
+        result.simplify_signs()
+    } else {
+        result
+    }
+}
 
-        // set kind of box based on final result
-        result.kinds[0] = new_kind;
+impl<L: BoxType + BoxAdd<R>, R: BoxType> Add<BoxValue<R>> for BoxValue<L> {
+    type Output = BoxValue<L::Output>;
 
-        result.sort_immediate_children();
-        result
+    /// Add two boxes
+    ///
+    /// Adding a box and an anti-box cancels the child boxes of the anti-box against the child
+    /// boxes of the box, e.g. the number box of five plus the anti-box of the number box of two
+    /// is the number box of three, in either order. The sum of two anti-boxes is the anti-box
+    /// of the merged child boxes.
+    ///
+    /// Multiplicities are arbitrary precision, so the addition never overflows. Use
+    /// [`BoxValue::checked_add`] to bound the merged multiplicities to `u32`.
+    fn add(self, rhs: BoxValue<R>) -> Self::Output {
+        add_boxes(self, rhs, true)
     }
 }
 
@@ -165,35 +216,34 @@ impl<L: BoxType + BoxAdd<R>, R: BoxType> Add<BoxValue<R>> for &BoxValue<L> {
     }
 }
 
-impl Add for BoxVariant {
-    type Output = Self;
+impl<T: BoxType> Sum for BoxValue<T> {
+    /// Add up the boxes starting from the zero box
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(BoxValue::zero().cast(), |acc, value| acc + value)
+    }
+}
 
-    fn add(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
+impl Sum for BoxVariant {
+    /// Add up the boxes starting from the zero box
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(BoxVariant::zero(), |acc, value| acc + value)
+    }
+}
+
+impl BoxVariant {
+    /// Add two boxes, or return an error if their kinds cannot be added
+    pub fn try_add(self, rhs: Self) -> Result<Self, TypeMismatchError> {
+        let value = match (self, rhs) {
             (BoxVariant::Empty(l), mut r) => {
                 let l_col = l.get_color(0);
                 let r_col = r.get_color(0);
-                match l_col + r_col {
-                    Color::Black => {
-                        r.set_color(0, Color::Black);
-                    }
-                    Color::Red => {
-                        r.set_color(0, Color::Red);
-                    }
-                }
+                r.set_color(0, l_col + r_col);
                 r
             }
             (mut l, BoxVariant::Empty(r)) => {
                 let l_col = l.get_color(0);
                 let r_col = r.get_color(0);
-                match l_col + r_col {
-                    Color::Black => {
-                        l.set_color(0, Color::Black);
-                    }
-                    Color::Red => {
-                        l.set_color(0, Color::Red);
-                    }
-                }
+                l.set_color(0, l_col + r_col);
                 l
             }
             (BoxVariant::Num(l), BoxVariant::Num(r)) => BoxVariant::repack_raw(l + r),
@@ -207,8 +257,85 @@ impl Add for BoxVariant {
             (BoxVariant::Multinum(l), BoxVariant::Multinum(r)) => BoxVariant::repack_raw(l + r),
             (BoxVariant::Vexel(l), BoxVariant::Vexel(r)) => BoxVariant::repack_raw(l + r),
             (BoxVariant::Maxel(l), BoxVariant::Maxel(r)) => BoxVariant::repack_raw(l + r),
-            (l, r) => panic!("Type Error: Cannot add {:?} to {:?}", l, r),
+            (l, r) => {
+                return Err(TypeMismatchError {
+                    op: "add",
+                    left: BoxKind::from(&l),
+                    right: BoxKind::from(&r),
+                });
+            }
+        };
+        Ok(value)
+    }
+}
+
+impl Add for BoxVariant {
+    type Output = Self;
+
+    /// Add two boxes
+    ///
+    /// # Panics
+    /// Panics if the kinds of the boxes cannot be added, use [`BoxVariant::try_add`] to get an
+    /// error instead.
+    fn add(self, rhs: Self) -> Self::Output {
+        self.try_add(rhs)
+            .unwrap_or_else(|err| panic!("Type Error: {}", err))
+    }
+}
+
+impl<L: BoxType> BoxValue<L> {
+    /// Add two boxes and check that all multiplicities of the sum fit into `u32`
+    ///
+    /// The sum is the same as with `+`, but an error is returned instead if a merged
+    /// multiplicity exceeds `u32::MAX`.
+    pub fn checked_add<R: BoxType>(
+        self,
+        other: BoxValue<R>,
+    ) -> Result<BoxValue<L::Output>, OverflowError>
+    where
+        L: BoxAdd<R>,
+    {
+        let result = self + other;
+        result.check_u32()?;
+        Ok(result)
+    }
+}
+
+impl<T: BoxType> BoxValue<T> {
+    /// Subtract the terms of another box without going below zero
+    ///
+    /// Every immediate child box of `other` reduces the multiplicity of the equal child box of
+    /// `self`, which is removed once its multiplicity would drop to zero or below. Child boxes
+    /// of `other` without an equal child box in `self` are ignored, so no anti-boxes are
+    /// created.
+    pub fn saturating_sub<U: BoxType>(self, other: BoxValue<U>) -> Self {
+        let mut unique_children: RapidHashMap<u64, BoxValue<AnyBox>> = RapidHashMap::default();
+        let mut result = BoxValue::<T>::new();
+        result.kinds.push(self.kinds[0]);
+        result.colors.push(self.colors[0]);
+        result.multiplicities.push(self.multiplicities[0].clone());
+        result.lengths.push(1);
+        self.add_child_boxes(&mut unique_children, false);
+
+        for child in other {
+            let hash = child.hash_content(unique_children.hasher());
+            if let Some(own) = unique_children.get_mut(&hash)
+                && own.is_eq_content(&child)
+                && own.get_color(0) == child.get_color(0)
+            {
+                let mul = own
+                    .get_multiplicity(0)
+                    .saturating_sub(child.get_multiplicity(0));
+                own.set_multiplicity(0, mul);
+            }
+        }
+
+        for child in unique_children.into_values() {
+            if child.get_multiplicity(0) != 0 {
+                result.extend(child);
+            }
         }
+        result.annihilate()
     }
 }
 
@@ -223,7 +350,9 @@ impl Sub for BoxVariant {
 #[cfg(test)]
 mod tests {
 
-    use crate::BoxVariant;
+    use malachite::Natural;
+
+    use crate::{BoxValue, BoxVariant, PolynumBox};
 
     #[test]
     fn test_add() {
@@ -260,4 +389,93 @@ mod tests {
         let exp = BoxVariant::from(1);
         assert_eq!(sum, exp);
     }
+
+    #[test]
+    fn test_sum() {
+        let values = vec![
+            BoxValue::from(3).cast::<PolynumBox>(),
+            BoxValue::alpha(),
+            BoxValue::from(-1).cast(),
+        ];
+        for value in values.clone() {
+            let sum = BoxValue::zero().cast::<PolynumBox>() + value.clone();
+            sum.assert_normalized();
+            assert!(sum.reduces_to(&value));
+        }
+
+        let sum: BoxValue<PolynumBox> = values.into_iter().sum();
+        sum.assert_normalized();
+        assert_eq!(sum, BoxValue::from(2) + BoxValue::alpha());
+
+        let empty: BoxValue<PolynumBox> = std::iter::empty().sum();
+        assert_eq!(empty, BoxValue::zero().cast());
+
+        let sum: BoxVariant = (1..=4).map(BoxVariant::from).sum();
+        assert_eq!(sum, BoxVariant::from(10));
+    }
+
+    #[test]
+    fn test_saturating_sub() {
+        assert_eq!(
+            BoxValue::from(3).saturating_sub(BoxValue::from(5)),
+            BoxValue::from(0)
+        );
+        assert_eq!(
+            BoxValue::from(5).saturating_sub(BoxValue::from(5)),
+            BoxValue::from(0)
+        );
+        assert_eq!(
+            BoxValue::from(5).saturating_sub(BoxValue::from(2)),
+            BoxValue::from(3)
+        );
+
+        // every term saturates on its own
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(4) + 2_u32 * alpha.clone();
+        let other = BoxValue::from(1) + 5_u32 * alpha.clone() + alpha.clone().pow(2);
+        assert_eq!(poly.saturating_sub(other), BoxValue::from(3).cast());
+    }
+
+    #[test]
+    fn test_add_anti() {
+        let sum = BoxValue::from(5) + BoxValue::from(2).into_anti();
+        assert_eq!(sum, BoxValue::from(3));
+
+        let sum = BoxValue::from(2).into_anti() + BoxValue::from(5);
+        assert_eq!(sum, BoxValue::from(3));
+
+        let sum = BoxValue::from(2) + BoxValue::from(5).into_anti();
+        assert_eq!(sum, BoxValue::from(-3));
+        assert_eq!(sum, BoxValue::from(5).into_anti() + BoxValue::from(2));
+
+        let sum = BoxValue::from(3) + BoxValue::from(3).into_anti();
+        assert_eq!(sum, BoxValue::from(0));
+
+        let sum = BoxVariant::from(3) + BoxVariant::from(3).into_anti();
+        assert_eq!(sum, BoxVariant::zero());
+
+        // the sum of two anti-boxes is an anti-box
+        let sum = BoxValue::from(3).into_anti() + BoxValue::from(3).into_anti();
+        assert_eq!(sum, BoxValue::from(6).into_anti());
+        assert_eq!(sum.total_value(), Some(-6));
+    }
+
+    #[test]
+    fn test_checked_add() {
+        let alpha = BoxValue::alpha();
+        let left = BoxValue::from(3) * &alpha + BoxValue::from(1);
+        let right = BoxValue::from(-5) * &alpha;
+        (left.clone() + right.clone()).assert_normalized();
+        assert_eq!(
+            left.clone().checked_add(right.clone()),
+            Ok(left.clone() + right)
+        );
+
+        let big = BoxValue::from(u32::MAX) * &alpha;
+        let err = big.clone().checked_add(alpha).unwrap_err();
+        assert_eq!(
+            err.multiplicity,
+            Natural::from(u32::MAX) + Natural::from(1_u32)
+        );
+    }
 }
@@ -0,0 +1,572 @@
+//! Annihilation of boxes against anti-boxes at every level of nesting
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::BuildHasher,
+};
+
+use malachite::Natural;
+use rapidhash::RapidHashMap;
+
+use crate::{AnyBox, BoxKind, BoxType, BoxValue, add::merge_child};
+
+/// A box whose children are being reduced
+struct Frame {
+    row: usize,
+    next: usize,
+    end: usize,
+    children: Vec<(BoxValue<AnyBox>, u32)>,
+}
+
+impl Frame {
+    fn new<T: BoxType>(value: &BoxValue<T>, row: usize) -> Self {
+        Self {
+            row,
+            next: row + 1,
+            end: row + value.lengths[row] as usize,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// A cancellation of a box against an equal anti-box during annihilation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CancellationEvent {
+    /// Nesting level of the box whose children cancelled, zero for the root
+    pub level: usize,
+    /// The cancelled child box as a box with multiplicity one
+    pub term: BoxValue<AnyBox>,
+    /// Multiplicity that was removed from both the box and the anti-box
+    pub amount: Natural,
+}
+
+impl<T: BoxType> BoxValue<T> {
+    /// Annihilate boxes against anti-boxes at every level of nesting
+    ///
+    /// Equal child boxes are merged by adding their multiplicities, and a box cancels against
+    /// an equal anti-box, just as in box addition. Children with multiplicity zero are removed
+    /// and the kind of a number box is derived from its depth afterwards.
+    ///
+    /// The children of a box are reduced before the box itself. This uses an explicit stack
+    /// instead of recursion, so arbitrarily deep boxes can be reduced.
+    pub fn annihilate(self) -> Self {
+        self.annihilate_inner(None)
+    }
+
+    /// Return the annihilated box and keep the original
+    ///
+    /// The reduction only reads the rows of the box and builds the result from the reduced
+    /// children, so the unreduced box can still be shown next to the result without copying it
+    /// first. Equal to `self.clone().annihilate()`.
+    pub fn annihilate_ref(&self) -> Self {
+        self.annihilate_inner(None)
+    }
+
+    /// Annihilate the box and log every cancellation of a box against an anti-box
+    ///
+    /// The events are ordered as the cancellations are performed, so cancellations in nested
+    /// boxes come before those in their parents. The reduced box equals
+    /// [`BoxValue::annihilate`].
+    pub fn annihilate_traced(self) -> (Self, Vec<CancellationEvent>) {
+        let mut events = Vec::new();
+        let result = self.annihilate_inner(Some(&mut events));
+        (result, events)
+    }
+
+    fn annihilate_inner(&self, mut trace: Option<&mut Vec<CancellationEvent>>) -> Self {
+        let mut stack = vec![Frame::new(self, 0)];
+        loop {
+            let frame = stack.last_mut().expect("stack holds at least the root");
+            if frame.next < frame.end {
+                let row = frame.next;
+                frame.next += self.lengths[row] as usize;
+                stack.push(Frame::new(self, row));
+                continue;
+            }
+
+            let frame = stack.pop().expect("stack holds at least the root");
+            let trace = trace.as_deref_mut().map(|events| (stack.len(), events));
+            let (node, depth) = self.reduce_row(frame.row, frame.children, trace);
+            match stack.last_mut() {
+                Some(parent) => parent.children.push((node, depth)),
+                None => return node.cast(),
+            }
+        }
+    }
+
+    /// Annihilate the box and memoize the reduced subboxes in a cache
+    ///
+    /// The cache maps subboxes before reduction to their reduced form, both with multiplicity
+    /// one. A subbox found in the cache is not descended into again, so boxes that repeat the
+    /// same large subbox, e.g. results of [`BoxValue::pow`], are reduced only once. The same
+    /// cache can be passed to several calls. The result equals [`BoxValue::annihilate`].
+    pub fn annihilate_with_cache<S: BuildHasher>(
+        self,
+        cache: &mut HashMap<BoxValue<AnyBox>, BoxValue<AnyBox>, S>,
+    ) -> Self {
+        let mut stack = vec![Frame::new(&self, 0)];
+        let mut keys = vec![self.cache_key(0)];
+        loop {
+            let frame = stack.last_mut().expect("stack holds at least the root");
+            if frame.next < frame.end {
+                let row = frame.next;
+                frame.next += self.lengths[row] as usize;
+
+                let key = self.cache_key(row);
+                if let Some(cached) = cache.get(&key) {
+                    let mut node = cached.clone();
+                    node.set_multiplicity(0, self.multiplicities[row].clone());
+                    let depth = node.depth();
+                    frame.children.push((node, depth));
+                } else {
+                    stack.push(Frame::new(&self, row));
+                    keys.push(key);
+                }
+                continue;
+            }
+
+            let frame = stack.pop().expect("stack holds at least the root");
+            let key = keys.pop().expect("every frame has a key");
+            let (node, depth) = self.reduce_row(frame.row, frame.children, None);
+
+            let mut cached = node.clone();
+            cached.set_multiplicity(0, Natural::from(1_u32));
+            cache.insert(key, cached);
+
+            match stack.last_mut() {
+                Some(parent) => parent.children.push((node, depth)),
+                None => return node.cast(),
+            }
+        }
+    }
+
+    /// Merge one pair of equal immediate child boxes
+    ///
+    /// The first pair of children with equal content is merged like in box addition, so a box
+    /// and an equal anti-box cancel each other. A child whose multiplicity drops to zero is
+    /// removed. Returns the box and whether a pair was merged, in which case the children are
+    /// sorted and the kind of a number box is updated. Calling this until nothing changes gives
+    /// the same result as [`BoxValue::annihilate`] if the children are already reduced.
+    pub fn simplify_once(self) -> (Self, bool) {
+        let mut children: Vec<BoxValue<AnyBox>> = self.clone().into_iter().collect();
+        let pair = (0..children.len()).find_map(|i| {
+            (i + 1..children.len())
+                .find(|&j| children[i].is_eq_content(&children[j]))
+                .map(|j| (i, j))
+        });
+        let Some((i, j)) = pair else {
+            return (self, false);
+        };
+
+        let other = children.remove(j);
+        let mut unique_children: RapidHashMap<u64, BoxValue<AnyBox>> = RapidHashMap::default();
+        merge_child(&mut unique_children, children[i].clone());
+        merge_child(&mut unique_children, other);
+        let merged = unique_children
+            .into_values()
+            .next()
+            .expect("merged children are equal");
+        if merged.get_multiplicity(0) == 0 {
+            children.remove(i);
+        } else {
+            children[i] = merged;
+        }
+
+        let mut result = BoxValue::new();
+        result.kinds.push(self.kinds[0]);
+        result.colors.push(self.colors[0]);
+        result.multiplicities.push(self.multiplicities[0].clone());
+        result.lengths.push(1);
+        for child in children {
+            result.extend(child);
+        }
+        result.update_number_kind();
+        result.sort_immediate_children();
+        (result, true)
+    }
+
+    /// Test if both boxes are equal after annihilation
+    ///
+    /// Unlike `==`, which compares the raw structure, this treats boxes that only differ by
+    /// boxes cancelling against anti-boxes as equal.
+    pub fn reduces_to<U: BoxType>(&self, other: &BoxValue<U>) -> bool {
+        self.clone().annihilate().cast::<AnyBox>() == other.clone().annihilate().cast::<AnyBox>()
+    }
+
+    /// Return the canonical form of the box
+    ///
+    /// The box is annihilated, which merges equal child boxes, removes child boxes with
+    /// multiplicity zero and sorts the children at every level. If the root is an anti-box
+    /// afterwards, its sign is moved into the immediate children, since an anti-box holds the
+    /// same value as the box of its inverted children. Two boxes are equal in value if and only
+    /// if their canonical forms are equal.
+    pub fn canonical(self) -> Self {
+        self.annihilate().simplify_signs()
+    }
+
+    /// Assert that the box is annihilated at every level of nesting
+    ///
+    /// Panics if a child box has multiplicity zero or if two immediate children of the same box
+    /// have equal content, which annihilation would have merged or cancelled. This documents
+    /// the invariants results of box arithmetic hold. The check only runs with debug
+    /// assertions enabled.
+    pub fn assert_normalized(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        for row in 0..self.kinds.len() {
+            let end = row + self.lengths[row] as usize;
+            let mut children = Vec::new();
+            let mut child = row + 1;
+            while child < end {
+                assert!(
+                    self.multiplicities[child] != 0,
+                    "child box at row {child} has multiplicity zero"
+                );
+                if let Some(&other) = children
+                    .iter()
+                    .find(|&&other| self.is_eq_content_at(other, child))
+                {
+                    panic!("child boxes at rows {other} and {child} have equal content");
+                }
+                children.push(child);
+                child += self.lengths[child] as usize;
+            }
+        }
+    }
+
+    /// Test if the subboxes at two rows are equal apart from the color and multiplicity
+    pub(crate) fn is_eq_content_at(&self, left: usize, right: usize) -> bool {
+        let len = self.lengths[left] as usize;
+        if self.lengths[right] as usize != len {
+            return false;
+        }
+        let (left, right) = (left..left + len, right..right + len);
+        self.kinds[left.clone()] == self.kinds[right.clone()]
+            && self.lengths[left.clone()] == self.lengths[right.clone()]
+            && self.colors[left.start + 1..left.end] == self.colors[right.start + 1..right.end]
+            && self.multiplicities[left.start + 1..left.end]
+                == self.multiplicities[right.start + 1..right.end]
+    }
+
+    /// Copy the subbox at the given row with multiplicity one
+    fn cache_key(&self, row: usize) -> BoxValue<AnyBox> {
+        let range = row..row + self.lengths[row] as usize;
+        let mut key = BoxValue::new_with(
+            self.kinds[range.clone()].to_vec(),
+            self.colors[range.clone()].to_vec(),
+            self.multiplicities[range.clone()].to_vec(),
+            self.lengths[range].to_vec(),
+        );
+        key.set_multiplicity(0, Natural::from(1_u32));
+        key
+    }
+
+    /// Construct the box at the given row out of its reduced children and return its depth
+    ///
+    /// If a trace is given, every cancellation among the children is logged with the level.
+    fn reduce_row(
+        &self,
+        row: usize,
+        children: Vec<(BoxValue<AnyBox>, u32)>,
+        mut trace: Option<(usize, &mut Vec<CancellationEvent>)>,
+    ) -> (BoxValue<AnyBox>, u32) {
+        let mut unique_children: RapidHashMap<u64, BoxValue<AnyBox>> = RapidHashMap::default();
+        let mut depths: RapidHashMap<u64, u32> = RapidHashMap::default();
+        for (child, depth) in children {
+            let hash = child.hash_content(unique_children.hasher());
+            depths.insert(hash, depth);
+            if let Some((level, events)) = trace.as_mut()
+                && let Some(other) = unique_children.get(&hash)
+                && child.is_eq_content(other)
+                && child.get_color(0) != other.get_color(0)
+            {
+                let amount = child.get_multiplicity(0).min(other.get_multiplicity(0));
+                if amount > 0 {
+                    let mut term = child.as_box();
+                    term.set_multiplicity(0, Natural::from(1_u32));
+                    events.push(CancellationEvent {
+                        level: *level,
+                        term,
+                        amount,
+                    });
+                }
+            }
+            merge_child(&mut unique_children, child);
+        }
+
+        let mut result = BoxValue::<AnyBox>::new();
+        result.kinds.push(self.kinds[row]);
+        result.colors.push(self.colors[row]);
+        result.multiplicities.push(self.multiplicities[row].clone());
+        result.lengths.push(1);
+
+        let mut depth = 0;
+        for (hash, child) in unique_children {
+            if child.get_multiplicity(0) == 0 {
+                continue;
+            }
+            depth = depth.max(depths[&hash] + 1);
+            result.extend(child);
+        }
+
+        if is_number_kind(result.kinds[0]) {
+            result.kinds[0] = BoxKind::get_kind_from_depth(depth);
+        }
+
+        result.sort_immediate_children();
+        (result, depth)
+    }
+}
+
+/// Replace every box by its canonical form and remove duplicates
+///
+/// Boxes are compared by their canonical forms, so boxes that are equal in value but differ in
+/// structure collapse into one entry. The first occurrence of every value is kept in order.
+pub fn dedup_canonical<T: BoxType + Eq>(boxes: Vec<BoxValue<T>>) -> Vec<BoxValue<T>> {
+    let mut seen = HashSet::new();
+    boxes
+        .into_iter()
+        .map(BoxValue::canonical)
+        .filter(|value| seen.insert(value.clone()))
+        .collect()
+}
+
+/// Test if the kind belongs to the hierarchy of numbers, polynumbers and multinumbers
+pub(crate) fn is_number_kind(kind: BoxKind) -> bool {
+    matches!(
+        kind,
+        BoxKind::Empty | BoxKind::Num | BoxKind::Polynum | BoxKind::Multinum
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+
+    use malachite::Natural;
+
+    use crate::{
+        AnyBox, BoxKind, BoxValue, NumBox,
+        annihilate::{CancellationEvent, dedup_canonical},
+    };
+
+    /// Return a number box holding `positive` empty boxes and `negative` empty anti-boxes that
+    /// are not merged yet
+    fn unreduced_num(positive: u32, negative: u32) -> BoxValue<AnyBox> {
+        let mut num = BoxValue::from(0).cast::<AnyBox>();
+        num.set_kind(0, BoxKind::Num);
+        num.extend_with_mul(BoxValue::zero(), positive);
+        num.extend_with_mul(BoxValue::anti_zero(), negative);
+        num
+    }
+
+    #[test]
+    fn test_annihilate() {
+        let mut num = unreduced_num(5, 2);
+        num.extend_with_mul(BoxValue::zero(), 1_u32);
+        assert_eq!(num.annihilate(), BoxValue::from(4).cast());
+
+        let mut inner = BoxValue::empty();
+        inner.extend(BoxValue::zero());
+        inner.extend(BoxValue::anti_zero());
+        let mut outer = BoxValue::empty();
+        outer.extend_with_mul(inner, 3_u32);
+        let mut exp = BoxValue::empty();
+        exp.extend_with_mul(BoxValue::empty(), 3_u32);
+        assert_eq!(outer.annihilate(), exp);
+
+        let poly = BoxValue::from(2) + 3_u32 * BoxValue::alpha() * BoxValue::alpha();
+        assert_eq!(poly.clone().annihilate(), poly);
+    }
+
+    #[test]
+    fn test_annihilate_ref() {
+        let num = unreduced_num(5, 2);
+
+        let reduced = num.annihilate_ref();
+        assert_eq!(reduced, BoxValue::from(3).cast());
+        assert_eq!(num.terms().count(), 2);
+        assert_eq!(reduced, num.annihilate());
+    }
+
+    #[test]
+    fn test_assert_normalized() {
+        let sum = BoxValue::from(3) + BoxValue::from(-5);
+        sum.assert_normalized();
+        let poly = (BoxValue::from(1) + BoxValue::alpha()).pow(3);
+        poly.assert_normalized();
+    }
+
+    #[test]
+    #[should_panic(expected = "equal content")]
+    fn test_assert_normalized_duplicate() {
+        let num = unreduced_num(2, 1);
+        num.assert_normalized();
+    }
+
+    #[test]
+    #[should_panic(expected = "multiplicity zero")]
+    fn test_assert_normalized_zero() {
+        let mut inner = BoxValue::from(1).cast::<AnyBox>();
+        inner.extend_with_mul(BoxValue::zero(), 0_u32);
+        let mut outer = BoxValue::empty();
+        outer.extend(inner);
+        outer.assert_normalized();
+    }
+
+    #[test]
+    fn test_annihilate_deep() {
+        let mut deep = BoxValue::from(-1).cast::<AnyBox>();
+        deep.extend(BoxValue::zero());
+        for _ in 0..5000 {
+            deep = deep.wrap::<AnyBox>(1_u32);
+        }
+
+        let mut exp = BoxValue::zero().cast::<AnyBox>();
+        for _ in 0..5000 {
+            exp = exp.wrap::<AnyBox>(1_u32);
+        }
+        assert_eq!(deep.annihilate(), exp);
+    }
+
+    #[test]
+    fn test_annihilate_traced() {
+        let inner = unreduced_num(5, 2);
+
+        let mut outer = BoxValue::empty();
+        outer.extend(inner);
+        outer.extend_with_mul(BoxValue::alpha(), 3_u32);
+        outer.extend_with_mul(BoxValue::alpha().into_anti(), 1_u32);
+
+        let (result, events) = outer.clone().annihilate_traced();
+        assert_eq!(result, outer.annihilate());
+        assert_eq!(
+            events,
+            vec![
+                CancellationEvent {
+                    level: 1,
+                    term: BoxValue::zero().cast(),
+                    amount: Natural::from(2_u32),
+                },
+                CancellationEvent {
+                    level: 0,
+                    term: BoxValue::alpha().cast(),
+                    amount: Natural::from(1_u32),
+                },
+            ]
+        );
+
+        let (_, events) = BoxValue::from(3).annihilate_traced();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_annihilate_with_cache() {
+        let pair = unreduced_num(4, 1);
+        let poly = (pair.cast::<NumBox>() + BoxValue::alpha()).pow(4);
+
+        let mut repeated = BoxValue::empty();
+        for mul in 1..=3_u32 {
+            repeated.extend_with_mul(poly.clone(), mul);
+            repeated.extend_with_mul(poly.clone().wrap::<AnyBox>(1_u32), mul);
+        }
+
+        let mut cache = HashMap::new();
+        let cached = repeated.clone().annihilate_with_cache(&mut cache);
+        assert_eq!(cached, repeated.clone().annihilate());
+        assert!(cache.contains_key(&poly.clone().cast()));
+
+        let again = repeated.clone().annihilate_with_cache(&mut cache);
+        assert_eq!(again, repeated.annihilate());
+        assert_eq!(
+            poly.clone().annihilate_with_cache(&mut cache),
+            poly.annihilate()
+        );
+    }
+
+    #[test]
+    fn test_reduces_to() {
+        let mut pair = BoxValue::empty();
+        pair.extend(BoxValue::anti_empty());
+        pair.extend(BoxValue::empty());
+        assert_ne!(pair, BoxValue::empty());
+        assert!(pair.reduces_to(&BoxValue::empty()));
+
+        let num = unreduced_num(3, 1);
+        assert_ne!(num, BoxValue::from(2).cast());
+        assert!(num.reduces_to(&BoxValue::from(2)));
+        assert!(!num.reduces_to(&BoxValue::from(3)));
+
+        let mut nested = BoxValue::empty();
+        nested.extend(pair.clone());
+        nested.extend(pair.into_anti());
+        assert_ne!(nested, BoxValue::empty());
+        assert!(nested.reduces_to(&BoxValue::empty()));
+    }
+
+    #[test]
+    fn test_canonical() {
+        let exp = BoxValue::from(-3).cast::<AnyBox>();
+
+        let unmerged = unreduced_num(5, 8);
+
+        let constructions = vec![
+            BoxValue::from(-3).cast::<AnyBox>(),
+            BoxValue::from(3).into_anti().cast(),
+            (BoxValue::from(2) + BoxValue::from(5).into_anti()).cast(),
+            unmerged,
+        ];
+        for value in constructions {
+            assert_eq!(value.canonical(), exp);
+        }
+    }
+
+    #[test]
+    fn test_dedup_canonical() {
+        let boxes = vec![
+            BoxValue::from(2).cast::<AnyBox>(),
+            BoxValue::alpha().cast(),
+            (BoxValue::from(5) + BoxValue::from(-3)).cast(),
+            BoxValue::from(-2).into_anti().cast(),
+            (BoxValue::from(1) + BoxValue::from(1)).cast(),
+            BoxValue::from(-2).cast(),
+            (BoxValue::alpha() + BoxValue::from(0)).cast(),
+        ];
+        let exp = vec![
+            BoxValue::from(2).cast::<AnyBox>(),
+            BoxValue::alpha().cast(),
+            BoxValue::from(-2).cast(),
+        ];
+        assert_eq!(dedup_canonical(boxes), exp);
+    }
+
+    #[test]
+    fn test_simplify_once() {
+        let mut value = BoxValue::from(0).cast::<AnyBox>();
+        value.set_kind(0, BoxKind::Polynum);
+        value.extend_with_mul(BoxValue::zero(), 3_u32);
+        value.extend_with_mul(BoxValue::from(1).into_anti(), 2_u32);
+        value.extend_with_mul(BoxValue::anti_zero(), 5_u32);
+        value.extend_with_mul(BoxValue::from(1), 2_u32);
+        value.extend_with_mul(BoxValue::from(2), 1_u32);
+
+        let (step, changed) = value.clone().simplify_once();
+        assert!(changed);
+        assert_eq!(step.len(), 4);
+
+        let (step, changed) = step.simplify_once();
+        assert!(changed);
+        assert_eq!(step.len(), 2);
+
+        let (fixpoint, changed) = step.simplify_once();
+        assert!(!changed);
+        assert_eq!(fixpoint, value.annihilate());
+
+        let pair = unreduced_num(1, 1);
+        let (step, changed) = pair.simplify_once();
+        assert!(changed);
+        assert_eq!(step, BoxValue::zero().cast());
+    }
+}
@@ -0,0 +1,200 @@
+//! Incremental construction of boxes out of their terms
+
+use std::marker::PhantomData;
+
+use malachite::Natural;
+use rapidhash::RapidHashMap;
+
+use crate::{AnyBox, BoxKind, BoxType, BoxValue, Color, add::merge_child};
+
+/// Builder that collects the terms of a box and returns it in canonical layout
+///
+/// Every term is annihilated when it is added, equal terms accumulate their multiplicities and
+/// cancel against equal anti-box terms, and terms with multiplicity zero are ignored. The built
+/// box has sorted children and its root gets the kind of the number hierarchy matching its
+/// depth, just like the result of box arithmetic.
+#[derive(Debug, Clone)]
+pub struct BoxBuilder<T: BoxType> {
+    color: Color,
+    unique_children: RapidHashMap<u64, BoxValue<AnyBox>>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: BoxType> BoxBuilder<T> {
+    /// Start building a box
+    pub fn new() -> Self {
+        Self::with_color(Color::Black)
+    }
+
+    /// Start building an anti-box
+    pub fn new_anti() -> Self {
+        Self::with_color(Color::Red)
+    }
+
+    fn with_color(color: Color) -> Self {
+        Self {
+            color,
+            unique_children: RapidHashMap::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Add a term with the given multiplicity as an immediate child box
+    pub fn add_term<U: BoxType>(mut self, term: BoxValue<U>, mul: impl Into<Natural>) -> Self {
+        let mul = mul.into();
+        if mul == 0 {
+            return self;
+        }
+
+        let mut term = term.annihilate().cast::<AnyBox>();
+        term.set_multiplicity(0, mul);
+        merge_child(&mut self.unique_children, term);
+        self
+    }
+
+    /// Build the box out of the collected terms
+    pub fn build(self) -> BoxValue<T> {
+        let mut result = BoxValue::new();
+        result.kinds.push(BoxKind::Empty);
+        result.colors.push(self.color);
+        result.multiplicities.push(Natural::from(1_u32));
+        result.lengths.push(1);
+
+        let mut depth = 0;
+        for child in self.unique_children.into_values() {
+            if child.get_multiplicity(0) == 0 {
+                continue;
+            }
+            depth = depth.max(child.depth() + 1);
+            result.extend(child);
+        }
+
+        result.kinds[0] = BoxKind::get_kind_from_depth(depth);
+        result.sort_immediate_children();
+        result
+    }
+}
+
+impl<T: BoxType> BoxValue<T> {
+    /// Construct a box out of terms and their multiplicities in a single pass
+    ///
+    /// The terms are collected with a [`BoxBuilder`], so equal terms are merged and the
+    /// children are sorted once at the end instead of after every insertion. Any collection of
+    /// pairs works, e.g. a `BTreeMap` from terms to multiplicities, and terms with multiplicity
+    /// zero are dropped.
+    pub fn from_terms<U: BoxType, N: Into<Natural>>(
+        terms: impl IntoIterator<Item = (BoxValue<U>, N)>,
+    ) -> Self {
+        Self::collect_terms(BoxBuilder::new(), terms)
+    }
+
+    /// Construct an anti-box out of terms and their multiplicities in a single pass
+    pub fn from_terms_anti<U: BoxType, N: Into<Natural>>(
+        terms: impl IntoIterator<Item = (BoxValue<U>, N)>,
+    ) -> Self {
+        Self::collect_terms(BoxBuilder::new_anti(), terms)
+    }
+
+    /// Construct a box of the given color out of terms and their multiplicities
+    ///
+    /// This is the inverse of [`BoxValue::into_parts`].
+    pub fn from_parts<U: BoxType, N: Into<Natural>>(
+        color: Color,
+        terms: impl IntoIterator<Item = (BoxValue<U>, N)>,
+    ) -> Self {
+        match color {
+            Color::Black => Self::from_terms(terms),
+            Color::Red => Self::from_terms_anti(terms),
+        }
+    }
+
+    fn collect_terms<U: BoxType, N: Into<Natural>>(
+        builder: BoxBuilder<T>,
+        terms: impl IntoIterator<Item = (BoxValue<U>, N)>,
+    ) -> Self {
+        terms
+            .into_iter()
+            .fold(builder, |builder, (term, mul)| builder.add_term(term, mul))
+            .build()
+    }
+}
+
+impl<T: BoxType> Default for BoxBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::BTreeMap;
+
+    use crate::{AnyBox, BoxValue, PolynumBox, builder::BoxBuilder};
+
+    #[test]
+    fn test_builder() {
+        let alpha_sq = BoxValue::alpha() * BoxValue::alpha();
+        let poly = BoxBuilder::<PolynumBox>::new()
+            .add_term(BoxValue::from(0), 2_u32)
+            .add_term(BoxValue::from(2), 3_u32)
+            .add_term(BoxValue::from(1), 0_u32)
+            .add_term(BoxValue::from(0), 4_u32)
+            .add_term(BoxValue::from(2).into_anti(), 1_u32)
+            .build();
+
+        let exp = BoxValue::from(6) + 2_u32 * alpha_sq;
+        assert_eq!(poly, exp);
+        assert_eq!(poly.clone().annihilate(), poly);
+    }
+
+    #[test]
+    fn test_builder_numbers() {
+        assert_eq!(BoxBuilder::new().build(), BoxValue::zero());
+        assert_eq!(BoxBuilder::new_anti().build(), BoxValue::anti_zero());
+
+        let three = BoxBuilder::new()
+            .add_term(BoxValue::zero(), 5_u32)
+            .add_term(BoxValue::anti_zero(), 2_u32)
+            .build();
+        assert_eq!(three, BoxValue::from(3));
+    }
+
+    #[test]
+    fn test_from_terms() {
+        let terms: Vec<_> = (0..100_u32)
+            .map(|i| (BoxValue::from(i % 7), i + 1))
+            .collect();
+
+        let mut incremental = BoxValue::zero().cast::<PolynumBox>();
+        for (term, mul) in terms.clone() {
+            incremental.insert(term, mul);
+        }
+        let value = BoxValue::<PolynumBox>::from_terms(terms.clone());
+        assert_eq!(value, incremental);
+        assert_eq!(value.len(), 7);
+
+        let anti = BoxValue::<PolynumBox>::from_terms_anti(terms);
+        assert_eq!(anti, incremental.into_anti());
+    }
+
+    #[test]
+    fn test_from_terms_map() {
+        let mut map: BTreeMap<BoxValue<AnyBox>, u32> = BTreeMap::new();
+        map.insert(BoxValue::zero().cast(), 4);
+        map.insert(BoxValue::from(1).cast(), 0);
+        map.insert(BoxValue::from(2).cast(), 3);
+        // reduces to the constant term, so the two are merged
+        let mut unreduced = BoxValue::from(1).cast::<AnyBox>();
+        unreduced.extend(BoxValue::anti_zero());
+        map.insert(unreduced, 1);
+
+        let value = BoxValue::<PolynumBox>::from_terms(map.clone());
+        let exp = BoxValue::from(5) + 3_u32 * BoxValue::alpha().pow(2);
+        assert_eq!(value, exp);
+        assert!(!value.contains(&BoxValue::from(1)));
+
+        let anti = BoxValue::<PolynumBox>::from_terms_anti(map);
+        assert_eq!(anti, exp.into_anti());
+    }
+}
@@ -0,0 +1,235 @@
+//! Compact binary encoding of boxes
+//!
+//! A box is encoded as the number of its rows followed by the rows in order. Each row is a tag
+//! byte holding the kind and the color, followed by the multiplicity and the length as LEB128
+//! varints.
+
+use std::fmt::{self, Display, Formatter};
+
+use malachite::{Natural, base::num::conversion::traits::PowerOf2Digits};
+
+use crate::{AnyBox, BoxKind, BoxType, BoxValue, BoxVariant, Color, dispatch};
+
+/// Error returned when decoding a box from bytes fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of a box
+    UnexpectedEnd,
+    /// A tag byte does not encode a kind and a color
+    InvalidTag(u8),
+    /// The lengths of the rows do not describe properly nested boxes
+    InvalidLength,
+    /// There are bytes left after the encoded box
+    TrailingBytes,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            DecodeError::InvalidTag(tag) => write!(f, "invalid tag byte {:#04x}", tag),
+            DecodeError::InvalidLength => write!(f, "invalid box length"),
+            DecodeError::TrailingBytes => write!(f, "trailing bytes after box"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+fn write_varint(out: &mut Vec<u8>, value: &Natural) {
+    let digits: Vec<u8> = value.to_power_of_2_digits_asc(7);
+    if digits.is_empty() {
+        out.push(0);
+        return;
+    }
+
+    let last = digits.len() - 1;
+    for (i, digit) in digits.into_iter().enumerate() {
+        if i < last {
+            out.push(digit | 0x80);
+        } else {
+            out.push(digit);
+        }
+    }
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<Natural, DecodeError> {
+    let mut digits = Vec::new();
+    loop {
+        let (&byte, rest) = bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+        *bytes = rest;
+        digits.push(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    Natural::from_power_of_2_digits_asc(7, digits.into_iter()).ok_or(DecodeError::InvalidLength)
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<u32, DecodeError> {
+    let value = read_varint(bytes)?;
+    u32::try_from(&value).map_err(|_| DecodeError::InvalidLength)
+}
+
+fn encode_tag(kind: BoxKind, color: Color) -> u8 {
+    let color = match color {
+        Color::Black => 0,
+        Color::Red => 1,
+    };
+    ((kind as u8) << 1) | color
+}
+
+fn decode_tag(tag: u8) -> Result<(BoxKind, Color), DecodeError> {
+    let kind = BoxKind::from_repr((tag >> 1) as usize).ok_or(DecodeError::InvalidTag(tag))?;
+    let color = match tag & 1 {
+        0 => Color::Black,
+        _ => Color::Red,
+    };
+    Ok((kind, color))
+}
+
+impl<T: BoxType> BoxValue<T> {
+    /// Encode the box in the compact binary format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_varint(&mut out, &Natural::from(self.kinds.len()));
+        for i in 0..self.kinds.len() {
+            out.push(encode_tag(self.kinds[i], self.colors[i]));
+            write_varint(&mut out, &self.multiplicities[i]);
+            write_varint(&mut out, &Natural::from(self.lengths[i]));
+        }
+        out
+    }
+
+    /// Test if the lengths of the rows describe properly nested boxes
+    pub(crate) fn is_well_formed(&self) -> bool {
+        let num_rows = self.lengths.len();
+        if num_rows == 0 || self.lengths[0] as usize != num_rows {
+            return false;
+        }
+
+        // end indices of the boxes enclosing the current row
+        let mut ends: Vec<usize> = Vec::new();
+        for (i, &len) in self.lengths.iter().enumerate() {
+            while ends.last() == Some(&i) {
+                ends.pop();
+            }
+
+            let end = i + len as usize;
+            let parent_end = ends.last().copied().unwrap_or(num_rows);
+            if len == 0 || end > parent_end {
+                return false;
+            }
+            ends.push(end);
+        }
+        true
+    }
+}
+
+impl BoxValue<AnyBox> {
+    /// Decode a box from the compact binary format
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut input = bytes;
+        let num_rows = read_u32(&mut input)? as usize;
+
+        // every row takes at least three bytes, which bounds the allocation
+        if num_rows > input.len() / 3 {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+
+        let mut result = BoxValue::new();
+        for _ in 0..num_rows {
+            let (&tag, rest) = input.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+            input = rest;
+
+            let (kind, color) = decode_tag(tag)?;
+            result.kinds.push(kind);
+            result.colors.push(color);
+            result.multiplicities.push(read_varint(&mut input)?);
+            result.lengths.push(read_u32(&mut input)?);
+        }
+
+        if !input.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        if !result.is_well_formed() {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        Ok(result)
+    }
+}
+
+impl BoxVariant {
+    /// Encode the box in the compact binary format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        dispatch!(self => to_bytes())
+    }
+
+    /// Decode a box from the compact binary format
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        BoxValue::from_bytes(bytes).map(BoxVariant::repack_raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{AnyBox, BoxValue, BoxVariant, bytes::DecodeError, maxel};
+
+    #[test]
+    fn test_bytes() {
+        let boxes = vec![
+            BoxVariant::zero(),
+            BoxVariant::anti_zero(),
+            BoxVariant::from(300),
+            BoxVariant::from(-7),
+            BoxVariant::from(1) + BoxVariant::anti_alpha() * BoxVariant::alpha(),
+            BoxVariant::from(u64::MAX) * BoxVariant::from(u64::MAX),
+            BoxVariant::from(2).wrap::<AnyBox>(5_u32).into_anti(),
+            maxel![[[1, 1], [1, 2], [2, 2]]],
+        ];
+
+        for value in boxes {
+            let bytes = value.to_bytes();
+            assert_eq!(BoxVariant::from_bytes(&bytes), Ok(value));
+        }
+    }
+
+    #[test]
+    fn test_bytes_corrupt() {
+        let bytes = BoxValue::alpha().to_bytes();
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert_eq!(
+            BoxValue::from_bytes(truncated),
+            Err(DecodeError::UnexpectedEnd)
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            BoxValue::from_bytes(&trailing),
+            Err(DecodeError::TrailingBytes)
+        );
+
+        let mut bad_tag = bytes.clone();
+        bad_tag[1] = 0xff;
+        assert_eq!(
+            BoxValue::from_bytes(&bad_tag),
+            Err(DecodeError::InvalidTag(0xff))
+        );
+
+        // the child claims to be longer than its parent
+        let mut bad_len = bytes.clone();
+        let last = bad_len.len() - 1;
+        bad_len[last] = 3;
+        assert_eq!(
+            BoxValue::from_bytes(&bad_len),
+            Err(DecodeError::InvalidLength)
+        );
+
+        assert_eq!(BoxValue::from_bytes(&[]), Err(DecodeError::UnexpectedEnd));
+    }
+}
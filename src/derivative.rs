@@ -4,6 +4,10 @@ use crate::{BoxKind, BoxValue, MultinumBox, PolynumBox};
 
 impl BoxValue<PolynumBox> {
     /// Derivative of a polynumber
+    ///
+    /// Each term `c * alpha^k` becomes `(c * k) * alpha^(k - 1)` and the constant term is
+    /// dropped, so the derivative of a constant is zero. Children that are not terms of a
+    /// polynumber are dropped as well.
     pub fn derivative(self) -> Self {
         let mut result = BoxValue::<PolynumBox>::new();
         result.kinds.push(self.get_kind(0));
@@ -37,6 +41,8 @@ impl BoxValue<PolynumBox> {
 
         if max_depth == 1 {
             result.set_kind(0, BoxKind::Num);
+        } else if max_depth == 0 {
+            result.set_kind(0, BoxKind::Empty);
         }
         result
     }
@@ -105,6 +111,18 @@ mod tests {
         let der = poly.derivative();
         let exp = BoxValue::from(3).cast::<PolynumBox>();
         assert_eq!(der, exp);
+
+        let poly: BoxValue<PolynumBox> = BoxValue::from(5) + 3_u32 * BoxValue::alpha().pow(2);
+        assert_eq!(poly.derivative(), 6_u32 * BoxValue::alpha());
+
+        let poly: BoxValue<PolynumBox> =
+            BoxValue::from(1) + BoxValue::from(-2) * BoxValue::alpha().pow(2);
+        assert_eq!(poly.derivative(), BoxValue::from(-4) * BoxValue::alpha());
+
+        let constant = BoxValue::from(5).cast::<PolynumBox>();
+        assert_eq!(constant.derivative(), BoxValue::zero().cast());
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(zero.derivative(), BoxValue::zero().cast());
     }
 
     #[test]
@@ -1,9 +1,9 @@
 use std::fmt::Display;
 
 use colored::Colorize;
-use malachite::Natural;
+use malachite::{Integer, Natural};
 
-use crate::{AnyBox, BoxKind, BoxType, BoxValue, BoxVariant};
+use crate::{AnyBox, BoxKind, BoxType, BoxValue, BoxVariant, Color, PolynumBox};
 
 /// Helper function to display multiplicities as subscripts
 fn to_subscript(num: Natural) -> String {
@@ -38,6 +38,174 @@ impl<T: BoxType> std::fmt::Display for BoxValue<T> {
     }
 }
 
+impl<T: BoxType> std::fmt::Debug for BoxValue<T> {
+    /// Format the box as a nested list of its kinds
+    ///
+    /// Anti-boxes are tagged with `anti` and multiplicities other than one follow the kind.
+    /// `{:?}` prints the box on a single line, e.g. `Num[Empty ×3, anti Empty]`, while `{:#?}`
+    /// prints one box per line and indents every child below its parent.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pretty = f.alternate();
+
+        // end indices of the boxes enclosing the current row and whether a child was written
+        let mut parents: Vec<(usize, bool)> = Vec::new();
+        for i in 0..self.kinds.len() {
+            while parents.last().is_some_and(|&(end, _)| end <= i) {
+                parents.pop();
+                if !pretty {
+                    write!(f, "]")?;
+                }
+            }
+
+            if pretty {
+                if i > 0 {
+                    writeln!(f)?;
+                }
+                write!(f, "{}", "    ".repeat(parents.len()))?;
+            }
+            if let Some((_, written)) = parents.last_mut() {
+                if !pretty && *written {
+                    write!(f, ", ")?;
+                }
+                *written = true;
+            }
+
+            if self.colors[i] == Color::Red {
+                write!(f, "anti ")?;
+            }
+            write!(f, "{:?}", self.kinds[i])?;
+            if self.multiplicities[i] != 1 {
+                write!(f, " ×{}", self.multiplicities[i])?;
+            }
+
+            if self.lengths[i] > 1 {
+                if !pretty {
+                    write!(f, "[")?;
+                }
+                parents.push((i + self.lengths[i] as usize, false));
+            }
+        }
+        if !pretty {
+            write!(f, "{}", "]".repeat(parents.len()))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: BoxType> BoxValue<T> {
+    /// Export the box as a Graphviz DOT digraph
+    ///
+    /// Every box is a node labeled with its kind, anti-boxes are drawn in red. The edges point
+    /// from a box to its immediate children and are labeled with the multiplicity of the child.
+    /// Nodes are named after their row, so the output follows the stored order of the box.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph box {\n");
+
+        // rows and end indices of the boxes enclosing the current row
+        let mut parents: Vec<(usize, usize)> = Vec::new();
+        for i in 0..self.kinds.len() {
+            while parents.last().is_some_and(|&(_, end)| end <= i) {
+                parents.pop();
+            }
+
+            let color = match self.colors[i] {
+                Color::Black => "black",
+                Color::Red => "red",
+            };
+            out.push_str(&format!(
+                "    n{} [label=\"{:?}\", shape=box, color={}, fontcolor={}];\n",
+                i, self.kinds[i], color, color
+            ));
+
+            if let Some(&(parent, _)) = parents.last() {
+                out.push_str(&format!(
+                    "    n{} -> n{} [label=\"{}\"];\n",
+                    parent, i, self.multiplicities[i]
+                ));
+            }
+            parents.push((i, i + self.lengths[i] as usize));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl<T: BoxType> BoxValue<T> {
+    /// Render the box as LaTeX
+    ///
+    /// Integer boxes become numbers and polynumbers become polynomials in `\alpha` with the
+    /// highest degree first, e.g. `3\alpha^{2} + 6`. Any other box is rendered as its
+    /// children in the order of [`BoxValue::terms_descending`] between `\left[` and
+    /// `\right]`, each preceded by its multiplicity if it is greater than one. Anti-boxes are
+    /// overlined.
+    pub fn to_latex(&self) -> String {
+        let kind = self.get_kind(0);
+        let is_number = matches!(kind, BoxKind::Empty | BoxKind::Num | BoxKind::Polynum);
+
+        let mut black = self.clone().cast::<PolynumBox>();
+        black.set_color(0, Color::Black);
+        let latex = match black.degree_terms() {
+            Some(terms) if is_number => polynomial_latex(&terms),
+            _ => {
+                let children: Vec<String> = self
+                    .terms_descending()
+                    .into_iter()
+                    .map(|(child, mul)| {
+                        let child = child.to_owned().to_latex();
+                        if *mul > 1 {
+                            format!("{}{}", mul, child)
+                        } else {
+                            child
+                        }
+                    })
+                    .collect();
+                format!("\\left[ {} \\right]", children.join(", "))
+            }
+        };
+
+        if self.is_anti() {
+            format!("\\overline{{{}}}", latex)
+        } else {
+            latex
+        }
+    }
+}
+
+/// Render signed coefficients sorted by degree as a polynomial in alpha, highest degree first
+fn polynomial_latex(terms: &[(Natural, Integer)]) -> String {
+    if terms.is_empty() {
+        return "0".to_string();
+    }
+
+    let mut out = String::new();
+    for (i, (degree, coeff)) in terms.iter().rev().enumerate() {
+        let abs = coeff.unsigned_abs_ref();
+        let sign = if *coeff < 0 { "-" } else { "+" };
+        if i == 0 {
+            if *coeff < 0 {
+                out.push('-');
+            }
+        } else {
+            out.push_str(&format!(" {} ", sign));
+        }
+
+        if *degree == 0 {
+            out.push_str(&abs.to_string());
+            continue;
+        }
+        if *abs != 1 {
+            out.push_str(&abs.to_string());
+        }
+        if *degree == 1 {
+            out.push_str("\\alpha");
+        } else {
+            out.push_str(&format!("\\alpha^{{{}}}", degree));
+        }
+    }
+    out
+}
+
 impl Display for BoxVariant {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let kind = self.get_kind(0);
@@ -85,14 +253,16 @@ impl Display for BoxVariant {
         };
 
         write!(f, "{}", open)?;
+        let raw = self.clone().into_any_raw();
         let mut first = true;
-        for child in self.clone() {
+        for (child, mult) in raw.terms_descending() {
             if !first {
                 write!(f, ",")?;
             }
             first = false;
 
-            let mult = child.get_multiplicity(0);
+            let child = BoxVariant::repack_raw(child.to_owned());
+            let mult = mult.clone();
             if f.alternate() {
                 if mult > 1 {
                     write!(f, "{}", to_subscript(mult))?;
@@ -160,14 +330,15 @@ impl<T: BoxType> Display for BoxDisplay<T> {
         write!(f, "{}", open)?;
 
         let mut first = true;
-        for child in self.0.clone() {
+        for (child, mult) in self.0.terms_descending() {
             if !first {
                 write!(f, ",")?;
             }
             first = false;
 
+            let child = child.to_owned();
             let len = child.get_length(0);
-            let mult = child.get_multiplicity(0);
+            let mult = mult.clone();
             if len > 1 {
                 let child = BoxDisplay::new(child);
                 if f.alternate() {
@@ -215,7 +386,21 @@ impl<T: BoxType> Display for BoxDisplay<T> {
 #[cfg(test)]
 mod tests {
 
-    use crate::{AnyBox, BoxVariant, display::BoxDisplay, maxel, vexel};
+    use crate::{AnyBox, BoxValue, BoxVariant, display::BoxDisplay, maxel, vexel};
+
+    /// Remove the ANSI escape sequences that color anti-boxes
+    fn strip_colors(text: &str) -> String {
+        let mut result = String::new();
+        let mut chars = text.chars();
+        while let Some(ch) = chars.next() {
+            if ch == '\u{1b}' {
+                chars.by_ref().find(|&ch| ch == 'm');
+            } else {
+                result.push(ch);
+            }
+        }
+        result
+    }
 
     #[test]
     fn test_display() {
@@ -260,5 +445,72 @@ mod tests {
         let a = BoxDisplay::<AnyBox>::from(&a);
         println!("{a}");
         println!("{a:#}");
+        // terms are printed with the highest power of alpha first
+        let alpha = BoxVariant::alpha();
+        let poly = BoxVariant::from(6)
+            + BoxVariant::from(-1) * alpha.clone()
+            + 3_u32 * alpha.clone() * alpha;
+        assert_eq!(strip_colors(&format!("{poly:#}")), "⌊₃2,1,₆0⌋");
+        assert_eq!(
+            strip_colors(&format!("{:#}", BoxDisplay::<AnyBox>::from(&poly))),
+            "⌊₃⌊₂□⌋,⌊□⌋,₆□⌋"
+        );
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut inner = BoxValue::from(2).cast::<AnyBox>();
+        inner.extend(BoxValue::anti_zero());
+        let mut outer = BoxValue::empty();
+        outer.extend_with_mul(inner, 3_u32);
+        outer.extend(BoxValue::zero());
+
+        assert_eq!(
+            format!("{:?}", outer),
+            "Any[Num ×3[Empty ×2, anti Empty], Empty]"
+        );
+        assert_eq!(
+            format!("{:#?}", outer),
+            "Any\n    Num ×3\n        Empty ×2\n        anti Empty\n    Empty"
+        );
+        assert_eq!(format!("{:?}", BoxValue::from(-1)), "Num[anti Empty]");
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let value = BoxValue::from(1) + BoxValue::from(-2) * BoxValue::alpha();
+        let exp = "digraph box {
+    n0 [label=\"Polynum\", shape=box, color=black, fontcolor=black];
+    n1 [label=\"Empty\", shape=box, color=black, fontcolor=black];
+    n0 -> n1 [label=\"1\"];
+    n2 [label=\"Num\", shape=box, color=red, fontcolor=red];
+    n0 -> n2 [label=\"2\"];
+    n3 [label=\"Empty\", shape=box, color=black, fontcolor=black];
+    n2 -> n3 [label=\"1\"];
+}
+";
+        assert_eq!(value.to_dot(), exp);
+    }
+
+    #[test]
+    fn test_to_latex() {
+        assert_eq!(BoxValue::from(6).to_latex(), "6");
+        assert_eq!(BoxValue::from(-6).to_latex(), "-6");
+        assert_eq!(BoxValue::zero().to_latex(), "0");
+
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(6) + 3_u32 * alpha.clone().pow(2);
+        assert_eq!(poly.to_latex(), "3\\alpha^{2} + 6");
+        let poly = BoxValue::from(1) + BoxValue::from(-1) * alpha.clone();
+        assert_eq!(poly.to_latex(), "-\\alpha + 1");
+
+        let mut nested = BoxValue::empty();
+        nested.extend_with_mul(BoxValue::from(2).into_anti(), 3_u32);
+        nested.extend(alpha.wrap::<AnyBox>(1_u32));
+        let nested = nested.into_anti();
+        assert_eq!(
+            nested.to_latex(),
+            "\\overline{\\left[ 3\\overline{2}, \\left[ \\alpha \\right] \\right]}"
+        );
     }
 }
@@ -0,0 +1,207 @@
+//! Error type covering every failure of the crate
+//!
+//! The operations of the crate return their own specific errors, [`BoxAlgebraError`] wraps all
+//! of them so that callers combining several operations can propagate them with `?`.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use crate::{
+    bytes::DecodeError,
+    mul::{OverflowError, TypeMismatchError},
+    parser::EvalError,
+};
+
+/// Error of any operation of the crate
+#[derive(Debug)]
+pub enum BoxAlgebraError {
+    /// The configuration, for example the command line arguments, is invalid
+    Config(&'static str),
+    /// Reading or writing failed
+    Io(io::Error),
+    /// The input does not form an expression, holds an [`EvalError::Lex`] or [`EvalError::Parse`]
+    Parse(EvalError),
+    /// A well formed expression cannot be evaluated
+    Eval(EvalError),
+    /// Some lines of the input failed to parse
+    Check {
+        /// The number of lines that failed to parse
+        failures: usize,
+    },
+    /// A multiplicity exceeds the range of `u32`
+    Overflow(OverflowError),
+    /// The binary encoding of a box is invalid
+    Decode(DecodeError),
+    /// Serializing to or deserializing from JSON failed
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+}
+
+impl Display for BoxAlgebraError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BoxAlgebraError::Config(message) => write!(f, "invalid configuration: {}", message),
+            BoxAlgebraError::Io(err) => write!(f, "io error: {}", err),
+            BoxAlgebraError::Parse(err) | BoxAlgebraError::Eval(err) => write!(f, "{}", err),
+            BoxAlgebraError::Check { failures } => {
+                write!(f, "{} line(s) failed to parse", failures)
+            }
+            BoxAlgebraError::Overflow(err) => write!(f, "{}", err),
+            BoxAlgebraError::Decode(err) => write!(f, "decode error: {}", err),
+            #[cfg(feature = "serde")]
+            BoxAlgebraError::Json(err) => write!(f, "json error: {}", err),
+        }
+    }
+}
+
+impl Error for BoxAlgebraError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BoxAlgebraError::Config(_) | BoxAlgebraError::Check { .. } => None,
+            BoxAlgebraError::Io(err) => Some(err),
+            BoxAlgebraError::Parse(err) | BoxAlgebraError::Eval(err) => Some(err),
+            BoxAlgebraError::Overflow(err) => Some(err),
+            BoxAlgebraError::Decode(err) => Some(err),
+            #[cfg(feature = "serde")]
+            BoxAlgebraError::Json(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for BoxAlgebraError {
+    fn from(err: io::Error) -> Self {
+        BoxAlgebraError::Io(err)
+    }
+}
+
+impl From<EvalError> for BoxAlgebraError {
+    /// Lexer and parser errors become [`BoxAlgebraError::Parse`], all others
+    /// [`BoxAlgebraError::Eval`]
+    fn from(err: EvalError) -> Self {
+        match err {
+            EvalError::Lex { .. } | EvalError::Parse { .. } => BoxAlgebraError::Parse(err),
+            _ => BoxAlgebraError::Eval(err),
+        }
+    }
+}
+
+impl From<TypeMismatchError> for BoxAlgebraError {
+    /// A type mismatch is an evaluation error, as if it came from [`EvalError::TypeMismatch`]
+    fn from(err: TypeMismatchError) -> Self {
+        BoxAlgebraError::Eval(err.into())
+    }
+}
+
+impl From<OverflowError> for BoxAlgebraError {
+    fn from(err: OverflowError) -> Self {
+        BoxAlgebraError::Overflow(err)
+    }
+}
+
+impl From<DecodeError> for BoxAlgebraError {
+    fn from(err: DecodeError) -> Self {
+        BoxAlgebraError::Decode(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for BoxAlgebraError {
+    fn from(err: serde_json::Error) -> Self {
+        BoxAlgebraError::Json(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, io};
+
+    use malachite::Natural;
+
+    use crate::{
+        AnyBox, BoxKind, BoxValue, BoxVariant,
+        bytes::DecodeError,
+        error::BoxAlgebraError,
+        mul::{OverflowError, TypeMismatchError},
+        parser::{EvalError, evaluate_expr},
+    };
+
+    #[test]
+    fn test_display() {
+        let err = BoxAlgebraError::Config("missing input file");
+        assert_eq!(err.to_string(), "invalid configuration: missing input file");
+        assert!(err.source().is_none());
+
+        let err = BoxAlgebraError::Check { failures: 2 };
+        assert_eq!(err.to_string(), "2 line(s) failed to parse");
+        assert!(err.source().is_none());
+
+        let err = BoxAlgebraError::from(EvalError::UndefinedVar("x".to_string()));
+        assert!(matches!(err, BoxAlgebraError::Eval(_)));
+        assert_eq!(err.to_string(), "undefined variable 'x'");
+
+        let err = BoxAlgebraError::from(OverflowError {
+            multiplicity: Natural::from(u64::MAX),
+        });
+        assert!(matches!(err, BoxAlgebraError::Overflow(_)));
+        assert_eq!(
+            err.to_string(),
+            format!("multiplicity {} exceeds u32::MAX", u64::MAX)
+        );
+
+        let err = BoxAlgebraError::from(TypeMismatchError {
+            op: "add",
+            left: BoxKind::Set,
+            right: BoxKind::Num,
+        });
+        assert!(matches!(
+            err,
+            BoxAlgebraError::Eval(EvalError::TypeMismatch { op: "add", .. })
+        ));
+        assert_eq!(err.to_string(), "cannot add Set and Num");
+
+        let err = BoxAlgebraError::from(DecodeError::TrailingBytes);
+        assert!(matches!(err, BoxAlgebraError::Decode(_)));
+        assert_eq!(err.to_string(), "decode error: trailing bytes after box");
+        assert_eq!(
+            err.source().unwrap().to_string(),
+            "trailing bytes after box"
+        );
+    }
+
+    #[test]
+    fn test_source() {
+        let inner = io::Error::new(io::ErrorKind::NotFound, "no such file");
+        let err = BoxAlgebraError::from(inner);
+        assert!(matches!(err, BoxAlgebraError::Io(_)));
+        assert_eq!(err.to_string(), "io error: no such file");
+        let source = err.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<io::Error>().unwrap().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        // `?` converts the specific errors of the crate
+        fn evaluate(input: &str) -> Result<BoxVariant, BoxAlgebraError> {
+            let value = evaluate_expr(input)?;
+            BoxValue::<AnyBox>::from_bytes(&[])?;
+            Ok(value)
+        }
+        let err = evaluate("2 + * 3").unwrap_err();
+        assert!(matches!(
+            err,
+            BoxAlgebraError::Parse(EvalError::Parse { .. })
+        ));
+        assert!(err.to_string().starts_with("parse error at"));
+        let source = err.source().unwrap().downcast_ref::<EvalError>().unwrap();
+        assert!(matches!(source, EvalError::Parse { .. }));
+
+        let err = evaluate("2 + 3").unwrap_err();
+        assert!(matches!(
+            err,
+            BoxAlgebraError::Decode(DecodeError::UnexpectedEnd)
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 use malachite::{Integer, Natural, base::num::arithmetic::traits::UnsignedAbs};
 
-use crate::{BoxValue, BoxVariant, NumBox};
+use crate::{BoxKind, BoxType, BoxValue, BoxVariant, Color, NumBox};
 
 impl From<u32> for BoxValue<NumBox> {
     fn from(value: u32) -> Self {
@@ -157,3 +157,148 @@ impl From<Integer> for BoxVariant {
         zero.wrap::<NumBox>(value.unsigned_abs()).into()
     }
 }
+
+impl<T: BoxType> BoxValue<T> {
+    /// Return the integer represented by the box
+    ///
+    /// A box represents an integer if it has no children, which is zero, or if its only child
+    /// is an empty box. An empty anti-box stands for a negative integer. Returns `None` for
+    /// anti-boxes and for boxes with nesting or more than one term.
+    pub fn to_integer(&self) -> Option<Integer> {
+        if self.is_anti() {
+            return None;
+        }
+
+        match self.get_length(0) {
+            1 => Some(Integer::from(0)),
+            2 => {
+                let value = Integer::from(self.get_multiplicity(1));
+                match self.get_color(1) {
+                    Color::Black => Some(value),
+                    Color::Red => Some(-value),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the integer value of the canonical form of the box if it fits into an `i64`
+    ///
+    /// Unlike [`BoxValue::try_to_i64`], the box is canonicalized first, so boxes and anti-boxes
+    /// cancel each other. Returns `None` if the canonical form is not an integer.
+    pub fn total_value(&self) -> Option<i64> {
+        self.clone().canonical().try_to_i64()
+    }
+
+    /// Test if the box is a natural number, i.e. a box without children or whose only child is
+    /// an empty box
+    ///
+    /// This agrees with [`BoxValue::is_integer`] on the kinds of the boxes.
+    pub fn is_natural_number(&self) -> bool {
+        self.is_integer() && self.to_integer().is_some_and(|value| value >= 0)
+    }
+
+    /// Test if the box is an integer
+    ///
+    /// Unlike [`BoxValue::to_integer`], this also accepts the anti-box of an integer, which
+    /// stands for its negation. The box must be an empty box or a number, and its only child
+    /// an empty box.
+    pub fn is_integer(&self) -> bool {
+        let is_number = matches!(self.get_kind(0), BoxKind::Empty | BoxKind::Num);
+        match self.get_length(0) {
+            1 => is_number,
+            2 => is_number && self.get_kind(1) == BoxKind::Empty,
+            _ => false,
+        }
+    }
+
+    /// Return the natural number represented by the box if it fits into a `u32`
+    pub fn try_to_u32(&self) -> Option<u32> {
+        u32::try_from(&self.to_integer()?).ok()
+    }
+
+    /// Return the integer represented by the box if it fits into an `i64`
+    pub fn try_to_i64(&self) -> Option<i64> {
+        i64::try_from(&self.to_integer()?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use malachite::{Integer, Natural};
+
+    use crate::{AnyBox, BoxKind, BoxValue, Color};
+
+    #[test]
+    fn test_to_integer() {
+        assert_eq!(BoxValue::from(7).try_to_u32(), Some(7));
+        assert_eq!(BoxValue::empty().try_to_u32(), Some(0));
+        assert_eq!(BoxValue::alpha().try_to_u32(), None);
+        assert_eq!(BoxValue::from(u64::MAX).try_to_u32(), None);
+
+        assert_eq!(BoxValue::from(-7).try_to_u32(), None);
+        assert_eq!(BoxValue::from(-7).try_to_i64(), Some(-7));
+        assert_eq!(
+            BoxValue::from(Integer::from(-3)).to_integer(),
+            Some(Integer::from(-3))
+        );
+
+        let two_terms = BoxValue::from(1) + BoxValue::alpha();
+        assert_eq!(two_terms.try_to_i64(), None);
+        assert_eq!(BoxValue::from(2).into_anti().try_to_i64(), None);
+    }
+
+    #[test]
+    fn test_total_value() {
+        let positive = BoxValue::from(5) + BoxValue::from(3).into_anti();
+        assert_eq!(positive.total_value(), Some(2));
+        assert_eq!(positive.try_to_i64(), Some(2));
+
+        let negative = BoxValue::from(2) + BoxValue::from(6).into_anti();
+        assert_eq!(negative.total_value(), Some(-4));
+        assert_eq!(BoxValue::from(4).into_anti().total_value(), Some(-4));
+
+        let mut zero = BoxValue::from(0).cast::<AnyBox>();
+        zero.extend_with_mul(BoxValue::zero(), 3_u32);
+        zero.extend_with_mul(BoxValue::anti_zero(), 3_u32);
+        assert_eq!(zero.try_to_i64(), None);
+        assert_eq!(zero.total_value(), Some(0));
+
+        let poly = BoxValue::from(1) + BoxValue::alpha();
+        assert_eq!(poly.total_value(), None);
+    }
+
+    #[test]
+    fn test_is_integer() {
+        let two_terms = BoxValue::from(1) + BoxValue::alpha();
+        let values = [
+            (BoxValue::from(0), true, true),
+            (BoxValue::from(5), true, true),
+            (BoxValue::from(-5), false, true),
+            (BoxValue::from(5).into_anti(), false, true),
+            (BoxValue::alpha().cast(), false, false),
+            (two_terms.cast(), false, false),
+        ];
+        for (value, natural, integer) in values {
+            assert_eq!(value.is_natural_number(), natural, "{}", value);
+            assert_eq!(value.is_integer(), integer, "{}", value);
+        }
+
+        // a childless child of another kind is not an empty box
+        for kinds in [
+            vec![BoxKind::Num, BoxKind::Set],
+            vec![BoxKind::Set, BoxKind::Empty],
+            vec![BoxKind::Set, BoxKind::Set],
+        ] {
+            let value = BoxValue::<AnyBox>::new_with(
+                kinds,
+                vec![Color::Black; 2],
+                vec![Natural::from(1_u32), Natural::from(3_u32)],
+                vec![2, 1],
+            );
+            assert!(!value.is_integer(), "{:?}", value);
+            assert!(!value.is_natural_number(), "{:?}", value);
+        }
+    }
+}
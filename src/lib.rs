@@ -2,30 +2,46 @@ use malachite::Natural;
 use strum::EnumDiscriminants;
 
 use std::{
-    cmp::Ordering::Equal,
+    cmp::Ordering,
     // fmt::{self, Display, Formatter},
     hash::{BuildHasher, Hash, Hasher},
     marker::PhantomData,
-    ops::{Add, Mul},
+    ops::{Add, Index, Mul},
 };
 
 use rapidhash::fast::RandomState;
 
 pub mod add;
+pub mod annihilate;
+pub mod builder;
+pub mod bytes;
 pub mod derivative;
 pub mod display;
+pub mod error;
 pub mod from;
 pub mod function;
 pub mod maxel;
 pub mod mul;
+pub mod nested;
 pub mod parser;
+pub mod poly;
+pub mod polynum;
+#[cfg(test)]
+mod proptests;
 pub mod set;
+pub mod sign;
 pub mod store;
+pub mod structure;
 
 /// Kind of boxes that can exist in a store
-#[derive(Debug, Clone, Hash, PartialEq, Eq, EnumDiscriminants)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, EnumDiscriminants)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[strum_discriminants(name(BoxKind))]
-#[strum_discriminants(derive(Hash, PartialOrd, Ord))]
+#[strum_discriminants(derive(Hash, PartialOrd, Ord, strum::FromRepr))]
+#[cfg_attr(
+    feature = "serde",
+    strum_discriminants(derive(serde::Serialize, serde::Deserialize))
+)]
 pub enum BoxVariant {
     Any(BoxValue<AnyBox>),
     Empty(BoxValue<EmptyBox>),
@@ -109,25 +125,15 @@ impl BoxVariant {
     }
 
     #[inline]
-    pub fn set_kind(&mut self, idx: usize, kind: BoxKind) {
-        dispatch!(self => kinds[idx] = kind);
-    }
-
-    #[inline]
-    pub fn set_color(&mut self, idx: usize, col: Color) {
+    pub(crate) fn set_color(&mut self, idx: usize, col: Color) {
         dispatch!(self => colors[idx] = col);
     }
 
     #[inline]
-    pub fn set_multiplicity(&mut self, idx: usize, mul: Natural) {
+    pub(crate) fn set_multiplicity(&mut self, idx: usize, mul: Natural) {
         dispatch!(self => multiplicities[idx] = mul);
     }
 
-    #[inline]
-    pub fn set_length(&mut self, idx: usize, len: u32) {
-        dispatch!(self => lengths[idx] = len);
-    }
-
     #[inline]
     pub fn into_any_raw(self) -> BoxValue<AnyBox> {
         dispatch!(self => cast::<AnyBox>())
@@ -217,6 +223,13 @@ impl BoxVariant {
     }
 }
 
+impl Default for BoxVariant {
+    /// The default box is the zero
+    fn default() -> Self {
+        BoxVariant::zero()
+    }
+}
+
 /// Static conversion into [`BoxVariant`]
 pub trait IntoVariant: BoxType {
     fn into_variant(value: BoxValue<Self>) -> BoxVariant;
@@ -354,18 +367,25 @@ impl BoxType for SetBox {
     const KIND: BoxKind = BoxKind::Set;
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct BoxValue<T: BoxType> {
     pub(crate) kinds: Vec<BoxKind>,
     pub(crate) colors: Vec<Color>,
     pub(crate) multiplicities: Vec<Natural>,
     pub(crate) lengths: Vec<u32>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     _marker: PhantomData<T>,
 }
 
 impl<T: BoxType> Default for BoxValue<T> {
+    /// Return the empty box, so that every public box has a root row
     fn default() -> Self {
-        Self::new()
+        BoxValue::zero().cast()
     }
 }
 
@@ -379,10 +399,20 @@ impl From<Vec<BoxValue<AnyBox>>> for BoxValue<AnyBox> {
         for any_box in value {
             result.extend(any_box);
         }
-        result
+        // equal boxes of the vector are merged or cancel out
+        result.annihilate()
+    }
+}
+
+impl<T: BoxType> PartialEq for BoxValue<T> {
+    /// Test if the boxes are equal, see [`BoxValue::structural_eq`]
+    fn eq(&self, other: &Self) -> bool {
+        self.structural_eq(other)
     }
 }
 
+impl<T: BoxType> Eq for BoxValue<T> {}
+
 impl<T: BoxType> Hash for BoxValue<T> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.kinds.hash(state);
@@ -392,9 +422,41 @@ impl<T: BoxType> Hash for BoxValue<T> {
     }
 }
 
+impl<T: BoxType, U: BoxType> Index<&BoxValue<U>> for BoxValue<T> {
+    type Output = Natural;
+
+    /// Return the multiplicity of an immediate child box
+    ///
+    /// The term must match a child including its color, as in [`BoxValue::multiplicity_of`].
+    ///
+    /// # Panics
+    /// Panics if the term is not among the immediate child boxes.
+    fn index(&self, term: &BoxValue<U>) -> &Self::Output {
+        self.get(term)
+            .unwrap_or_else(|| panic!("term {} is not a child of the box", term))
+    }
+}
+
+impl<T: BoxType + Eq> PartialOrd for BoxValue<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: BoxType + Eq> Ord for BoxValue<T> {
+    /// Compare the rows of two boxes by kinds, colors, lengths and multiplicities
+    ///
+    /// This is the same order that is used to sort the children of a box, and two boxes compare
+    /// as equal exactly if they are equal.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rows(0, self.kinds.len())
+            .cmp_rows(&other.rows(0, other.kinds.len()))
+    }
+}
+
 impl<T: BoxType> BoxValue<T> {
-    /// Initialize an empty raw box
-    pub fn new() -> Self {
+    /// Initialize a raw box without any rows, the root row has to be pushed by the caller
+    pub(crate) fn new() -> Self {
         Self {
             kinds: Vec::new(),
             colors: Vec::new(),
@@ -405,7 +467,7 @@ impl<T: BoxType> BoxValue<T> {
     }
 
     /// Construct a box from the given vectors
-    pub fn new_with(
+    pub(crate) fn new_with(
         kinds: Vec<BoxKind>,
         colors: Vec<Color>,
         multiplicities: Vec<Natural>,
@@ -450,6 +512,19 @@ impl<T: BoxType> BoxValue<T> {
         hasher.finish()
     }
 
+    /// Test if two boxes have the same structure, colors and multiplicities at every row
+    ///
+    /// The cheap comparisons come first: the kinds and the lengths of all rows, which also
+    /// compares the number of rows, and the colors and multiplicities last. The comparison
+    /// stops at the first mismatch, so unequal boxes are usually told apart without comparing
+    /// multiplicities. The marker types of the boxes are not compared.
+    pub fn structural_eq<U: BoxType>(&self, other: &BoxValue<U>) -> bool {
+        self.kinds == other.kinds
+            && self.lengths == other.lengths
+            && self.colors == other.colors
+            && self.multiplicities == other.multiplicities
+    }
+
     /// Compare the content of the two boxes for equality
     pub fn is_eq_content(&self, other: &Self) -> bool {
         let left_len = self.get_length(0) as usize;
@@ -494,25 +569,8 @@ impl<T: BoxType> BoxValue<T> {
 
         // sort ranges
         child_ranges.sort_by(|&(start_a, len_a), &(start_b, len_b)| {
-            let range_a = start_a..(start_a + len_a);
-            let range_b = start_b..(start_b + len_b);
-
-            let kinds_cmp = self.kinds[range_a.clone()].cmp(&self.kinds[range_b.clone()]);
-            if kinds_cmp != Equal {
-                return kinds_cmp;
-            }
-
-            let col_cmp = self.colors[range_a.clone()].cmp(&self.colors[range_b.clone()]);
-            if col_cmp != Equal {
-                return col_cmp;
-            }
-
-            let len_cmp = self.lengths[range_a.clone()].cmp(&self.lengths[range_b.clone()]);
-            if len_cmp != Equal {
-                return len_cmp;
-            }
-
-            self.multiplicities[range_a].cmp(&self.multiplicities[range_b])
+            self.rows(start_a, len_a)
+                .cmp_rows(&self.rows(start_b, len_b))
         });
 
         // load staging buffers
@@ -545,8 +603,33 @@ impl<T: BoxType> BoxValue<T> {
         }
     }
 
+    /// Iterate over the row indices of the immediate children
+    pub(crate) fn child_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        let end = self.lengths.first().copied().unwrap_or(0) as usize;
+        let mut next = 1;
+        std::iter::from_fn(move || {
+            let idx = next;
+            if idx >= end {
+                return None;
+            }
+            next += self.lengths[idx] as usize;
+            Some(idx)
+        })
+    }
+
+    /// Borrow `len` rows of the box starting at `start`
+    pub(crate) fn rows(&self, start: usize, len: usize) -> BoxValueRef<'_> {
+        let range = start..(start + len);
+        BoxValueRef {
+            kinds: &self.kinds[range.clone()],
+            colors: &self.colors[range.clone()],
+            multiplicities: &self.multiplicities[range.clone()],
+            lengths: &self.lengths[range],
+        }
+    }
+
     /// Extend the box with another box
-    pub fn extend(&mut self, value: BoxValue<impl BoxType>) {
+    pub(crate) fn extend(&mut self, value: BoxValue<impl BoxType>) {
         if let Some(len) = self.lengths.get_mut(0) {
             *len += value.get_length(0);
         }
@@ -557,11 +640,251 @@ impl<T: BoxType> BoxValue<T> {
     }
 
     /// Extend the box with another box and multiplicity
-    pub fn extend_with_mul(&mut self, mut value: BoxValue<impl BoxType>, mul: impl Into<Natural>) {
+    pub(crate) fn extend_with_mul(
+        &mut self,
+        mut value: BoxValue<impl BoxType>,
+        mul: impl Into<Natural>,
+    ) {
         value.set_multiplicity(0, mul);
         self.extend(value);
     }
 
+    /// Iterate over the immediate child boxes and their multiplicities in stored order
+    ///
+    /// Results of box arithmetic keep their children sorted, so the terms are yielded in
+    /// ascending order.
+    pub fn terms(&self) -> impl Iterator<Item = (BoxValueRef<'_>, &Natural)> {
+        self.into_iter().map(|child| {
+            let multiplicities = child.multiplicities;
+            (child, &multiplicities[0])
+        })
+    }
+
+    /// Return the immediate child boxes and their multiplicities sorted in descending order
+    ///
+    /// The terms are compared by their content, ignoring the color and multiplicity of each
+    /// term, so the sign and coefficient of a term do not affect its position. For polynumbers
+    /// this sorts the terms by descending degree, e.g. `3α² - α + 6`, which is the order
+    /// `Display` and [`BoxValue::to_latex`] print them in.
+    pub fn terms_descending(&self) -> Vec<(BoxValueRef<'_>, &Natural)> {
+        let mut terms: Vec<_> = self.terms().collect();
+        terms.sort_by(|(left, _), (right, _)| right.cmp_content(left));
+        terms
+    }
+
+    /// Consume the box and iterate over its immediate child boxes and their multiplicities
+    ///
+    /// The multiplicity is moved out of each child box, which is left with multiplicity one.
+    pub fn into_terms(self) -> impl Iterator<Item = (BoxValue<AnyBox>, Natural)> {
+        self.into_iter().map(|mut child| {
+            let mul = std::mem::replace(&mut child.multiplicities[0], Natural::from(1_u32));
+            (child, mul)
+        })
+    }
+
+    /// Return the color of the box together with its immediate child boxes and multiplicities
+    ///
+    /// Unlike [`BoxValue::terms`] this keeps whether the box is an anti-box, so the parts are
+    /// enough to rebuild it.
+    pub fn parts(&self) -> (Color, impl Iterator<Item = (BoxValueRef<'_>, &Natural)>) {
+        (self.get_color(0), self.terms())
+    }
+
+    /// Consume the box and return its color together with its immediate child boxes and
+    /// multiplicities
+    ///
+    /// The box is rebuilt from the parts with [`BoxValue::from_parts`].
+    pub fn into_parts(self) -> (Color, impl Iterator<Item = (BoxValue<AnyBox>, Natural)>) {
+        (self.get_color(0), self.into_terms())
+    }
+
+    /// Return the multiplicity of a term among the immediate child boxes
+    ///
+    /// The term must match a child including its color, only the multiplicity of the term
+    /// itself is ignored. Returns zero if the term is absent, boxes nested deeper are not
+    /// searched.
+    pub fn multiplicity_of<U: BoxType>(&self, term: &BoxValue<U>) -> Natural {
+        self.child_indices()
+            .filter(|&idx| self.is_term_at(idx, term))
+            .map(|idx| &self.multiplicities[idx])
+            .sum()
+    }
+
+    /// Test if the child box at the given row matches the term apart from its multiplicity
+    fn is_term_at<U: BoxType>(&self, idx: usize, term: &BoxValue<U>) -> bool {
+        self.colors[idx] == term.colors[0] && self.is_content_at(idx, term)
+    }
+
+    /// Test if the child box at the index equals the term up to both colors and multiplicities
+    /// of their roots
+    fn is_content_at<U: BoxType>(&self, idx: usize, term: &BoxValue<U>) -> bool {
+        let len = self.lengths[idx] as usize;
+        let range = idx..idx + len;
+        len == term.kinds.len()
+            && self.kinds[range.clone()] == term.kinds[..]
+            && self.colors[idx + 1..idx + len] == term.colors[1..]
+            && self.lengths[range] == term.lengths[..]
+            && self.multiplicities[idx + 1..idx + len] == term.multiplicities[1..]
+    }
+
+    /// Return the multiplicity of the first immediate child box matching the term
+    ///
+    /// The term must match a child including its color, as in [`BoxValue::multiplicity_of`].
+    pub fn get<U: BoxType>(&self, term: &BoxValue<U>) -> Option<&Natural> {
+        self.child_indices()
+            .find(|&idx| self.is_term_at(idx, term))
+            .map(|idx| &self.multiplicities[idx])
+    }
+
+    /// Follow a path of terms down the nesting and return the box reached and its multiplicity
+    ///
+    /// Every term of the path must match a child of the box reached so far, as in
+    /// [`BoxValue::get`]. The empty path returns the box itself. Returns `None` if a term of
+    /// the path is absent.
+    pub fn box_at_path<U: BoxType>(
+        &self,
+        path: &[&BoxValue<U>],
+    ) -> Option<(BoxValueRef<'_>, &Natural)> {
+        let mut row = 0;
+        for term in path {
+            let end = row + self.lengths[row] as usize;
+            let mut idx = row + 1;
+            while idx < end && !self.is_term_at(idx, term) {
+                idx += self.lengths[idx] as usize;
+            }
+            if idx >= end {
+                return None;
+            }
+            row = idx;
+        }
+        Some((
+            self.rows(row, self.lengths[row] as usize),
+            &self.multiplicities[row],
+        ))
+    }
+
+    /// Return the number of immediate child boxes
+    pub fn len(&self) -> usize {
+        self.child_indices().count()
+    }
+
+    /// Test if the box has no child boxes
+    pub fn is_empty(&self) -> bool {
+        self.lengths.first().is_none_or(|&len| len <= 1)
+    }
+
+    /// Insert a term with the given multiplicity as an immediate child box
+    ///
+    /// The multiplicity is added to an equal child box if there is one, and cancels against an
+    /// equal child box of the opposite color, just as in box addition. Otherwise the term is
+    /// inserted, and the children are kept sorted. The kind of a number box is updated to the
+    /// new depth. Returns `false` and leaves the box untouched if the multiplicity is zero.
+    pub fn insert<U: BoxType>(&mut self, term: BoxValue<U>, mul: impl Into<Natural>) -> bool {
+        let mul = mul.into();
+        if mul == 0 {
+            return false;
+        }
+
+        let found = self
+            .child_indices()
+            .find(|&idx| self.is_content_at(idx, &term));
+        match found {
+            Some(idx) if self.colors[idx] == term.colors[0] => self.multiplicities[idx] += mul,
+            Some(idx) if self.multiplicities[idx] > mul => self.multiplicities[idx] -= mul,
+            Some(idx) if self.multiplicities[idx] < mul => {
+                self.multiplicities[idx] = mul - &self.multiplicities[idx];
+                self.colors[idx] = term.colors[0];
+            }
+            Some(idx) => {
+                let end = idx + self.lengths[idx] as usize;
+                self.kinds.drain(idx..end);
+                self.colors.drain(idx..end);
+                self.multiplicities.drain(idx..end);
+                self.lengths.drain(idx..end);
+                self.lengths[0] -= (end - idx) as u32;
+            }
+            None => self.extend_with_mul(term, mul),
+        }
+
+        // children are ordered by their multiplicities as well
+        self.update_number_kind();
+        self.sort_immediate_children();
+        true
+    }
+
+    /// Keep only the immediate child boxes whose term and multiplicity satisfy the predicate
+    ///
+    /// The term is passed with multiplicity one. The color of the root is preserved and the
+    /// kind of a number box is updated to the new depth.
+    pub fn retain_terms(self, pred: impl Fn(&BoxValue<AnyBox>, &Natural) -> bool) -> Self {
+        let mut result = BoxValue::new();
+        result.kinds.push(self.kinds[0]);
+        result.colors.push(self.colors[0]);
+        result.multiplicities.push(self.multiplicities[0].clone());
+        result.lengths.push(1);
+
+        for (term, mul) in self.into_terms() {
+            if pred(&term, &mul) {
+                result.extend_with_mul(term, mul);
+            }
+        }
+        result.update_number_kind();
+        result
+    }
+
+    /// Derive the kind of a number box from its nesting depth
+    fn update_number_kind(&mut self) {
+        if annihilate::is_number_kind(self.kinds[0]) {
+            self.kinds[0] = BoxKind::get_kind_from_depth(self.depth());
+        }
+    }
+
+    /// Test if a term is among the immediate child boxes
+    pub fn contains<U: BoxType>(&self, term: &BoxValue<U>) -> bool {
+        self.multiplicity_of(term) != 0
+    }
+
+    /// Return the terms among the immediate child boxes of both boxes
+    ///
+    /// Every shared term is returned once with multiplicity one, paired with its multiplicity
+    /// in this box and in the other box. Terms match including their color, as in
+    /// [`BoxValue::multiplicity_of`], and are returned in the order of this box.
+    pub fn common_terms<U: BoxType>(
+        &self,
+        other: &BoxValue<U>,
+    ) -> Vec<(BoxValue<AnyBox>, Natural, Natural)> {
+        let mut common: Vec<(BoxValue<AnyBox>, Natural, Natural)> = Vec::new();
+        for (term, mul) in self.clone().into_terms() {
+            let other_mul = other.multiplicity_of(&term);
+            if other_mul == 0 {
+                continue;
+            }
+            match common.iter_mut().find(|(seen, _, _)| *seen == term) {
+                Some((_, own, _)) => *own += mul,
+                None => common.push((term, mul, other_mul)),
+            }
+        }
+        common
+    }
+
+    /// Test if the box has exactly one immediate child box with multiplicity one
+    pub fn is_monomial(&self) -> bool {
+        let mut terms = self.terms();
+        matches!(terms.next(), Some((_, mul)) if *mul == 1) && terms.next().is_none()
+    }
+
+    /// Return the greatest immediate child box by [`Ord`] and its multiplicity
+    ///
+    /// The color of a child box is its sign and is ignored in the comparison, so for
+    /// polynumbers this is the term of the highest power of alpha whatever the sign of its
+    /// coefficient. The returned child box has multiplicity one and keeps its color. Returns
+    /// `None` if the box has no children.
+    pub fn leading_term(&self) -> Option<(BoxValue<AnyBox>, Natural)> {
+        self.clone()
+            .into_terms()
+            .max_by(|(left, _), (right, _)| left.as_box().cmp(&right.as_box()))
+    }
+
     /// Return the k-th kind if it exists
     ///
     /// # Panics
@@ -598,7 +921,7 @@ impl<T: BoxType> BoxValue<T> {
     ///
     /// # Panics
     /// Panics if the index is out of bounds.
-    pub fn set_kind(&mut self, index: usize, kind: BoxKind) {
+    pub(crate) fn set_kind(&mut self, index: usize, kind: BoxKind) {
         self.kinds[index] = kind;
     }
 
@@ -606,7 +929,7 @@ impl<T: BoxType> BoxValue<T> {
     ///
     /// # Panics
     /// Panics if the index is out of bounds.
-    pub fn set_color(&mut self, index: usize, col: Color) {
+    pub(crate) fn set_color(&mut self, index: usize, col: Color) {
         self.colors[index] = col;
     }
 
@@ -614,7 +937,7 @@ impl<T: BoxType> BoxValue<T> {
     ///
     /// # Panics
     /// Panics if the index is out of bounds.
-    pub fn set_multiplicity(&mut self, index: usize, mul: impl Into<Natural>) {
+    pub(crate) fn set_multiplicity(&mut self, index: usize, mul: impl Into<Natural>) {
         self.multiplicities[index] = mul.into();
     }
 
@@ -622,12 +945,12 @@ impl<T: BoxType> BoxValue<T> {
     ///
     /// # Panics
     /// Panics if the index is out of bounds.
-    pub fn set_length(&mut self, index: usize, len: u32) {
+    pub(crate) fn set_length(&mut self, index: usize, len: u32) {
         self.lengths[index] = len;
     }
 
     /// Remove the k-th row (without adjusting the lengths)
-    pub fn remove(&mut self, index: usize) {
+    pub(crate) fn remove(&mut self, index: usize) {
         self.kinds.remove(index);
         self.colors.remove(index);
         self.multiplicities.remove(index);
@@ -635,17 +958,44 @@ impl<T: BoxType> BoxValue<T> {
     }
 
     /// Wrap a box in another box
+    ///
+    /// The box is dropped if the multiplicity is zero, so the result has no children.
     pub fn wrap<U: BoxType>(mut self, mul: impl Into<Natural>) -> BoxValue<U> {
-        self.set_multiplicity(0, mul);
-
+        let mul = mul.into();
         let mut result = BoxValue::<U>::new();
         result.kinds.push(U::KIND);
         result.colors.push(Color::Black);
         result.multiplicities.push(Natural::from(1_u32));
         result.lengths.push(1);
 
-        result.extend(self);
+        if mul != 0 {
+            self.set_multiplicity(0, mul);
+            result.extend(self);
+        }
+
+        result
+    }
 
+    /// Wrap a box in `depth` nested boxes with multiplicity one
+    ///
+    /// The box is returned unchanged apart from its type if `depth` is zero.
+    pub fn wrap_n<U: BoxType>(self, depth: u32) -> BoxValue<U> {
+        let mut result = self.cast::<U>();
+        for _ in 0..depth {
+            result = result.wrap::<U>(1_u32);
+        }
+        result
+    }
+
+    /// Wrap a box in `depth` nested anti-boxes with multiplicity one
+    ///
+    /// Every wrapping box is an anti-box. The box is returned unchanged apart from its type if
+    /// `depth` is zero.
+    pub fn wrap_anti_n<U: BoxType>(self, depth: u32) -> BoxValue<U> {
+        let mut result = self.cast::<U>();
+        for _ in 0..depth {
+            result = result.wrap::<U>(1_u32).into_anti();
+        }
         result
     }
 
@@ -659,6 +1009,36 @@ impl<T: BoxType> BoxValue<T> {
         }
         self
     }
+
+    /// Reorganize the storage of the box without changing its value
+    ///
+    /// The rows are stored flat, so equal sub-boxes cannot share their rows. Instead, equal
+    /// children are merged into one child with the sum of their multiplicities at every level
+    /// and the spare capacity is released.
+    pub fn compact(&self) -> Self {
+        let mut result = self.clone().annihilate();
+        result.kinds.shrink_to_fit();
+        result.colors.shrink_to_fit();
+        result.multiplicities.shrink_to_fit();
+        result.lengths.shrink_to_fit();
+        result
+    }
+
+    /// Return the number of bytes allocated for the storage of the box
+    pub fn memory_footprint(&self) -> usize {
+        let limbs: usize = self
+            .multiplicities
+            .iter()
+            .map(|m| m.limb_count() as usize)
+            .filter(|&count| count > 1)
+            .sum();
+
+        self.kinds.capacity() * size_of::<BoxKind>()
+            + self.colors.capacity() * size_of::<Color>()
+            + self.multiplicities.capacity() * size_of::<Natural>()
+            + self.lengths.capacity() * size_of::<u32>()
+            + limbs * size_of::<u64>()
+    }
 }
 
 impl BoxValue<AnyBox> {
@@ -687,6 +1067,10 @@ impl BoxValue<AnyBox> {
 
 impl BoxValue<EmptyBox> {
     /// Construct an empty black box
+    ///
+    /// The empty box is the number zero and every other box is built out of it by wrapping:
+    /// the number `n` is a box holding the empty box `n` times, see [`BoxValue::one`] and
+    /// [`BoxValue::alpha`].
     pub fn zero() -> Self {
         BoxValue {
             kinds: vec![BoxKind::Empty],
@@ -711,6 +1095,9 @@ impl BoxValue<EmptyBox> {
 
 impl BoxValue<NumBox> {
     /// Construct the box representing the number one
+    ///
+    /// This is the empty box wrapped once. Adding boxes adds their children, so `from(n)` holds
+    /// the empty box `n` times and an anti-box child is a negative unit.
     pub fn one() -> Self {
         BoxValue {
             kinds: vec![BoxKind::Num, BoxKind::Empty],
@@ -735,6 +1122,9 @@ impl BoxValue<NumBox> {
 
 impl BoxValue<PolynumBox> {
     /// Construct the variable alpha
+    ///
+    /// This is the number one wrapped once. Multiplying boxes adds the children of their
+    /// children, so `alpha^k` holds the number `k` once, see [`BoxValue::alpha_pow`].
     pub fn alpha() -> Self {
         BoxValue {
             kinds: vec![BoxKind::Polynum, BoxKind::Num, BoxKind::Empty],
@@ -763,6 +1153,43 @@ impl BoxValue<PolynumBox> {
             _marker: std::marker::PhantomData,
         }
     }
+
+    /// Construct the n-th power of alpha directly
+    ///
+    /// This is the polynumber holding the number box of `n` once, the zeroth power is the
+    /// number box of one.
+    pub fn alpha_pow(n: u32) -> Self {
+        if n == 0 {
+            return BoxValue::one().cast();
+        }
+        BoxValue::from(n).wrap::<PolynumBox>(1_u32)
+    }
+
+    /// Construct the powers of alpha from the zeroth up to the n-th
+    ///
+    /// Element `k` is equal to [`BoxValue::alpha_pow`] of `k`. Every power is derived from the
+    /// previous one by incrementing the multiplicity of its exponent, without multiplying.
+    /// Returns an empty vector if the number of powers does not fit into a `usize`.
+    pub fn alpha_powers_up_to(n: u32) -> Vec<Self> {
+        let Some(len) = usize::try_from(n).ok().and_then(|n| n.checked_add(1)) else {
+            return Vec::new();
+        };
+        let mut powers = Vec::with_capacity(len);
+        powers.push(BoxValue::alpha_pow(0));
+        if n == 0 {
+            return powers;
+        }
+
+        let mut power = BoxValue::alpha();
+        for _ in 1..n {
+            let mut next = power.clone();
+            next.multiplicities[2] += Natural::from(1_u32);
+            powers.push(power);
+            power = next;
+        }
+        powers.push(power);
+        powers
+    }
 }
 
 impl BoxValue<MultinumBox> {
@@ -850,6 +1277,10 @@ impl<T: BoxType> IntoIterator for BoxValue<T> {
     type Item = BoxValue<AnyBox>;
     type IntoIter = BoxValueIter<T>;
 
+    /// Consume the box and iterate over its immediate child boxes in stored order
+    ///
+    /// Every child keeps its color and carries its multiplicity at the root, use
+    /// [`BoxValue::into_terms`] to get `(term, multiplicity)` pairs instead.
     fn into_iter(self) -> Self::IntoIter {
         BoxValueIter::new(self)
     }
@@ -881,6 +1312,10 @@ impl Iterator for BoxVariantIter {
     }
 }
 
+/// Borrowed rows of a box
+///
+/// A reference returned by [`BoxValue::terms`] covers a single child box. As an iterator it
+/// yields the consecutive boxes its rows hold.
 #[derive(Debug, Clone, Copy, Hash)]
 pub struct BoxValueRef<'a> {
     pub(crate) kinds: &'a [BoxKind],
@@ -889,10 +1324,79 @@ pub struct BoxValueRef<'a> {
     pub(crate) lengths: &'a [u32],
 }
 
+impl BoxValueRef<'_> {
+    /// Return the kind of the borrowed box, as stored in its root row
+    ///
+    /// # Panics
+    /// Panics if the reference holds no rows, e.g. after iterating all children.
+    pub fn kind(&self) -> BoxKind {
+        self.kinds[0]
+    }
+
+    /// Return the color of the borrowed box
+    ///
+    /// # Panics
+    /// Panics if the reference holds no rows.
+    pub fn color(&self) -> Color {
+        self.colors[0]
+    }
+
+    /// Test if the borrowed box is an anti-box
+    ///
+    /// # Panics
+    /// Panics if the reference holds no rows.
+    pub fn is_anti(&self) -> bool {
+        self.color() == Color::Red
+    }
+
+    /// Return the multiplicity of the borrowed box
+    ///
+    /// # Panics
+    /// Panics if the reference holds no rows.
+    pub fn multiplicity(&self) -> &Natural {
+        &self.multiplicities[0]
+    }
+
+    /// Copy the borrowed rows into an owned box
+    pub fn to_owned(&self) -> BoxValue<AnyBox> {
+        BoxValue::new_with(
+            self.kinds.to_vec(),
+            self.colors.to_vec(),
+            self.multiplicities.to_vec(),
+            self.lengths.to_vec(),
+        )
+    }
+
+    /// Compare two boxes by their content, ignoring the color and multiplicity of their roots
+    ///
+    /// Boxes of equal content are ordered by [`BoxValueRef::cmp_rows`].
+    pub(crate) fn cmp_content(&self, other: &Self) -> Ordering {
+        self.kinds
+            .cmp(other.kinds)
+            .then_with(|| self.colors[1..].cmp(&other.colors[1..]))
+            .then_with(|| self.lengths.cmp(other.lengths))
+            .then_with(|| self.multiplicities[1..].cmp(&other.multiplicities[1..]))
+            .then_with(|| self.cmp_rows(other))
+    }
+
+    /// Compare the rows of two boxes by kinds, colors, lengths and multiplicities
+    pub(crate) fn cmp_rows(&self, other: &Self) -> Ordering {
+        self.kinds
+            .cmp(other.kinds)
+            .then_with(|| self.colors.cmp(other.colors))
+            .then_with(|| self.lengths.cmp(other.lengths))
+            .then_with(|| self.multiplicities.cmp(other.multiplicities))
+    }
+}
+
 impl<'a, T: BoxType> IntoIterator for &'a BoxValue<T> {
     type Item = BoxValueRef<'a>;
     type IntoIter = BoxValueRef<'a>;
 
+    /// Iterate over borrowed immediate child boxes in stored order
+    ///
+    /// Every child keeps its color and carries its multiplicity at the root, use
+    /// [`BoxValue::terms`] to get `(term, multiplicity)` pairs instead.
     fn into_iter(self) -> Self::IntoIter {
         BoxValueRef {
             kinds: &self.kinds[1..],
@@ -931,6 +1435,7 @@ impl<'a> Iterator for BoxValueRef<'a> {
 
 /// Color of a box
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     Black,
     Red,
@@ -970,3 +1475,516 @@ impl Mul<Color> for Color {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use malachite::Natural;
+
+    use std::cmp::Ordering;
+
+    use crate::{AnyBox, BoxKind, BoxValue, BoxValueRef, BoxVariant, Color, NumBox, PolynumBox};
+
+    #[test]
+    fn test_terms() {
+        let poly =
+            BoxValue::from(1) + 2_u32 * BoxValue::alpha() + BoxValue::alpha() * BoxValue::alpha();
+
+        let kinds: Vec<_> = poly.terms().map(|(child, _)| child.kinds[0]).collect();
+        assert_eq!(kinds, vec![BoxKind::Empty, BoxKind::Num, BoxKind::Num]);
+
+        let mults: Vec<_> = poly.terms().map(|(_, mul)| mul.clone()).collect();
+        assert_eq!(
+            mults,
+            vec![
+                Natural::from(1_u32),
+                Natural::from(1_u32),
+                Natural::from(2_u32)
+            ]
+        );
+
+        let (term, mul) = poly.terms().last().unwrap();
+        assert_eq!(term.kind(), BoxKind::Num);
+        assert_eq!(term.color(), Color::Black);
+        assert!(!term.is_anti());
+        assert_eq!(term.multiplicity(), mul);
+        let owned: Vec<_> = poly.terms().map(|(term, _)| term.to_owned()).collect();
+        assert_eq!(owned, poly.clone().into_iter().collect::<Vec<_>>());
+        let negative = BoxValue::from(-2);
+        let (term, _) = negative.terms().next().unwrap();
+        assert!(term.is_anti());
+        assert_eq!(term.kind(), BoxKind::Empty);
+
+        let terms: Vec<_> = poly.into_terms().collect();
+        let exp = vec![
+            (BoxValue::zero().cast::<AnyBox>(), Natural::from(1_u32)),
+            (BoxValue::from(2).cast::<AnyBox>(), Natural::from(1_u32)),
+            (BoxValue::from(1).cast::<AnyBox>(), Natural::from(2_u32)),
+        ];
+        assert_eq!(terms, exp);
+    }
+
+    #[test]
+    fn test_terms_descending() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(6) + BoxValue::from(-1) * &alpha + 3_u32 * alpha.clone().pow(2);
+
+        let rows = |terms: Vec<(BoxValueRef<'_>, &Natural)>| -> Vec<_> {
+            terms
+                .into_iter()
+                .map(|(term, mul)| {
+                    (
+                        term.kinds.to_vec(),
+                        term.multiplicities.to_vec(),
+                        mul.clone(),
+                    )
+                })
+                .collect()
+        };
+        // the children of this box are stored by ascending degree
+        let sorted = BoxValue::from(1) + 2_u32 * alpha.clone() + 3_u32 * alpha.clone().pow(2);
+        let mut ascending = rows(sorted.terms().collect());
+        ascending.reverse();
+        let descending = rows(sorted.terms_descending());
+        assert_eq!(descending, ascending);
+
+        // the sign and the coefficient of a term do not change its position
+        let printed: Vec<String> = poly
+            .terms_descending()
+            .into_iter()
+            .map(|(term, mul)| {
+                format!(
+                    "{}{}:{}",
+                    if term.is_anti() { "-" } else { "" },
+                    mul,
+                    term.lengths[0]
+                )
+            })
+            .collect();
+        assert_eq!(printed, ["3:2", "-1:2", "6:1"]);
+        let degrees: Vec<_> = poly
+            .terms_descending()
+            .into_iter()
+            .map(|(term, _)| term.multiplicities.get(1).cloned())
+            .collect();
+        assert_eq!(
+            degrees,
+            [Some(Natural::from(2_u32)), Some(Natural::from(1_u32)), None]
+        );
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let poly = BoxValue::from(3) + BoxValue::from(-2) * BoxValue::alpha();
+
+        let mut children = Vec::new();
+        for child in &poly {
+            children.push((child.colors[0], child.multiplicities[0].clone()));
+        }
+        let exp = vec![
+            (Color::Black, Natural::from(3_u32)),
+            (Color::Red, Natural::from(2_u32)),
+        ];
+        assert_eq!(children, exp);
+
+        // the `(term, multiplicity)` pairs come from the terms adapters
+        let mut pairs = Vec::new();
+        for (term, mul) in poly.terms() {
+            pairs.push((term.is_anti(), mul.clone()));
+        }
+        let exp = vec![(false, Natural::from(3_u32)), (true, Natural::from(2_u32))];
+        assert_eq!(pairs, exp);
+        let owned_pairs: Vec<_> = poly.clone().into_terms().collect();
+        let exp = vec![
+            (BoxValue::zero().cast::<AnyBox>(), Natural::from(3_u32)),
+            (BoxValue::from(1).into_anti().cast(), Natural::from(2_u32)),
+        ];
+        assert_eq!(owned_pairs, exp);
+
+        let mut owned = Vec::new();
+        for child in poly {
+            owned.push(child);
+        }
+        let exp = vec![
+            BoxValue::zero().wrap::<AnyBox>(3_u32),
+            BoxValue::from(1).into_anti().wrap::<AnyBox>(2_u32),
+        ];
+        let exp: Vec<_> = exp.into_iter().flatten().collect();
+        assert_eq!(owned, exp);
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut num = BoxValue::from(0).cast::<AnyBox>();
+        num.set_kind(0, BoxKind::Num);
+        for _ in 0..100 {
+            num.extend(BoxValue::zero());
+        }
+
+        let compact = num.compact();
+        assert!(compact.reduces_to(&num));
+        assert_eq!(compact, BoxValue::from(100).cast());
+        assert!(compact.memory_footprint() < num.memory_footprint());
+    }
+
+    #[test]
+    fn test_structural_eq() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(1) + 3_u32 * alpha.clone().pow(2);
+        assert!(poly.structural_eq(&poly.clone()));
+        assert!(poly.structural_eq(&poly.clone().cast::<AnyBox>()));
+
+        // same layout, only the exponent deep inside differs
+        let other = BoxValue::from(1) + 3_u32 * alpha.clone().pow(3);
+        assert!(!poly.structural_eq(&other));
+        assert_ne!(poly, other);
+
+        let longer = poly.clone() + alpha.clone();
+        assert!(!poly.structural_eq(&longer));
+
+        // equality agrees with the order on large boxes
+        let large = (BoxValue::from(1) + alpha.clone()).pow(200);
+        let shifted = large.clone() + BoxValue::from(1);
+        assert_eq!(large == large.clone(), large.cmp(&large.clone()).is_eq());
+        assert_eq!(large == shifted, large.cmp(&shifted).is_eq());
+        assert!(!large.structural_eq(&shifted));
+    }
+
+    #[test]
+    fn test_parts() {
+        let poly = BoxValue::from(2) + BoxValue::from(-3) * BoxValue::alpha();
+        let anti = BoxValue::<PolynumBox>::from_parts(Color::Red, poly.clone().into_terms());
+        assert!(anti.is_anti());
+        assert_eq!(anti.terms().count(), 2);
+
+        for value in [poly, anti, BoxValue::anti_zero().cast()] {
+            let (color, terms) = value.parts();
+            assert_eq!(color, value.get_color(0));
+            assert_eq!(terms.count(), value.terms().count());
+
+            let (color, terms) = value.clone().into_parts();
+            assert_eq!(BoxValue::<PolynumBox>::from_parts(color, terms), value);
+        }
+    }
+
+    #[test]
+    fn test_common_terms() {
+        let alpha = BoxValue::alpha();
+        let left = BoxValue::from(2) + BoxValue::from(3) * &alpha + alpha.clone().pow(2);
+        let right = BoxValue::from(7) + BoxValue::from(-3) * &alpha + 4_u32 * alpha.clone().pow(2);
+
+        // the alpha terms differ in sign, so they are not shared
+        let exp = vec![
+            (
+                BoxValue::zero().cast(),
+                Natural::from(2_u32),
+                Natural::from(7_u32),
+            ),
+            (
+                BoxValue::from(2).cast(),
+                Natural::from(1_u32),
+                Natural::from(4_u32),
+            ),
+        ];
+        assert_eq!(left.common_terms(&right), exp);
+
+        let disjoint = BoxValue::from(5) * alpha.clone().pow(3);
+        assert!(left.common_terms(&disjoint).is_empty());
+        assert!(left.common_terms(&BoxValue::zero()).is_empty());
+    }
+
+    #[test]
+    fn test_multiplicity_of() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(2) + BoxValue::from(5) * alpha.clone() * alpha.clone();
+
+        let square = BoxValue::from(2).cast::<AnyBox>();
+        assert_eq!(poly.multiplicity_of(&square), 5_u32);
+        assert!(poly.contains(&square));
+        assert_eq!(poly.multiplicity_of(&BoxValue::zero()), 2_u32);
+
+        assert_eq!(poly.multiplicity_of(&BoxValue::from(1)), 0_u32);
+        assert!(!poly.contains(&BoxValue::anti_zero()));
+
+        // the empty box is only nested inside the children
+        let nested = BoxValue::from(3).wrap::<AnyBox>(1_u32);
+        assert!(!nested.contains(&BoxValue::zero()));
+        assert_eq!(nested.multiplicity_of(&BoxValue::zero()), 0_u32);
+    }
+
+    #[test]
+    fn test_default() {
+        assert_eq!(BoxVariant::default(), BoxVariant::zero());
+
+        #[derive(Debug, Default)]
+        struct Holder {
+            value: BoxVariant,
+            count: usize,
+        }
+
+        let holder = Holder::default();
+        assert_eq!(holder.value, BoxVariant::from(0));
+        assert_eq!(holder.count, 0);
+
+        let mut value = BoxValue::<AnyBox>::default();
+        assert_eq!(value, BoxValue::zero().cast());
+        assert!(!value.is_anti());
+        assert!(value.insert(BoxValue::zero(), 2_u32));
+        assert_eq!(value, BoxValue::from(2).cast());
+    }
+
+    #[test]
+    fn test_leading_term() {
+        let alpha = BoxValue::alpha();
+        assert!(alpha.is_monomial());
+        let (term, mul) = alpha.leading_term().unwrap();
+        assert_eq!(term, BoxValue::from(1).cast());
+        assert_eq!(mul, 1_u32);
+
+        let poly = BoxValue::from(4) + 3_u32 * alpha.clone() * alpha.clone() + alpha.clone();
+        assert!(!poly.is_monomial());
+        assert!(!(2_u32 * alpha.clone()).is_monomial());
+        let (term, mul) = poly.leading_term().unwrap();
+        assert_eq!(term, BoxValue::from(2).cast());
+        assert_eq!(mul, 3_u32);
+
+        // a negative coefficient of a lower power does not lead
+        let poly = alpha.clone().pow(2) + BoxValue::from(-1) * alpha.clone();
+        let (term, mul) = poly.leading_term().unwrap();
+        assert_eq!(term, BoxValue::from(2).cast());
+        assert_eq!(mul, 1_u32);
+        let poly = BoxValue::from(-2) * alpha.clone().pow(2) + alpha.clone();
+        let (term, mul) = poly.leading_term().unwrap();
+        assert_eq!(term, BoxValue::from(2).into_anti().cast());
+        assert_eq!(mul, 2_u32);
+
+        assert!(!BoxValue::empty().is_monomial());
+        assert_eq!(BoxValue::empty().leading_term(), None);
+    }
+
+    #[test]
+    fn test_wrap_n() {
+        for depth in 0..5 {
+            let mut exp = BoxValue::from(1).cast::<AnyBox>();
+            let mut anti_exp = exp.clone();
+            for _ in 0..depth {
+                exp = exp.wrap::<AnyBox>(1_u32);
+                anti_exp = anti_exp.wrap::<AnyBox>(1_u32).into_anti();
+            }
+            assert_eq!(BoxValue::from(1).wrap_n::<AnyBox>(depth), exp);
+            assert_eq!(BoxValue::from(1).wrap_anti_n::<AnyBox>(depth), anti_exp);
+        }
+
+        let three = BoxValue::from(1)
+            .wrap::<AnyBox>(1_u32)
+            .wrap::<AnyBox>(1_u32)
+            .wrap::<AnyBox>(1_u32);
+        assert_eq!(BoxValue::from(1).wrap_n(3), three);
+        assert_eq!(BoxValue::from(1).wrap_n::<AnyBox>(0).depth(), 1);
+    }
+
+    #[test]
+    fn test_generators() {
+        let one = BoxValue::zero().wrap::<NumBox>(1_u32);
+        assert_eq!(one, BoxValue::one());
+        assert_eq!(one, BoxValue::from(1));
+        assert_eq!(
+            BoxValue::zero().wrap::<NumBox>(1_u32).into_anti(),
+            BoxValue::anti_one()
+        );
+
+        let alpha = BoxValue::one().wrap::<PolynumBox>(1_u32);
+        assert_eq!(alpha, BoxValue::alpha());
+
+        let mut five = BoxValue::<NumBox>::new_with(
+            vec![BoxKind::Num],
+            vec![Color::Black],
+            vec![Natural::from(1_u32)],
+            vec![1],
+        );
+        five.extend_with_mul(BoxValue::zero(), 5_u32);
+        assert_eq!(five, BoxValue::from(5));
+        assert_eq!(BoxValue::from(3).wrap::<PolynumBox>(1_u32), alpha.pow(3));
+    }
+
+    #[test]
+    fn test_alpha_pow() {
+        for n in 0..6 {
+            assert_eq!(BoxValue::alpha_pow(n), BoxValue::alpha().pow(n));
+        }
+        assert_eq!(BoxValue::alpha_pow(1), BoxValue::alpha());
+        assert_eq!(BoxValue::alpha_pow(0), BoxValue::from(1).cast());
+    }
+
+    #[test]
+    fn test_alpha_powers_up_to() {
+        let powers = BoxValue::alpha_powers_up_to(6);
+        assert_eq!(powers.len(), 7);
+        for (k, power) in powers.iter().enumerate() {
+            assert_eq!(*power, BoxValue::alpha().pow(k as u32));
+        }
+        assert_eq!(
+            BoxValue::alpha_powers_up_to(0),
+            vec![BoxValue::alpha_pow(0)]
+        );
+    }
+
+    #[test]
+    fn test_index() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(2) + BoxValue::from(5) * alpha.clone() * alpha.clone();
+        assert_eq!(poly[&BoxValue::zero()], 2_u32);
+        assert_eq!(poly[&BoxValue::from(2)], 5_u32);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a child of the box")]
+    fn test_index_missing() {
+        let poly = BoxValue::from(2) + BoxValue::alpha();
+        let _ = poly[&BoxValue::from(3)];
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut value = BoxValue::from(0).cast::<PolynumBox>();
+        assert!(value.is_empty());
+        assert!(!value.insert(BoxValue::zero(), 0_u32));
+        assert_eq!(value.len(), 0);
+
+        assert!(value.insert(BoxValue::zero(), 2_u32));
+        assert!(value.insert(BoxValue::from(1), 3_u32));
+        assert!(value.insert(BoxValue::zero(), 1_u32));
+        assert_eq!(value.len(), 2);
+        assert_eq!(value.get(&BoxValue::zero()), Some(&Natural::from(3_u32)));
+        assert_eq!(value.get(&BoxValue::from(2)), None);
+        assert_eq!(value, BoxValue::from(3) + 3_u32 * BoxValue::alpha());
+
+        // adding to a child can change its position among the sorted children
+        let mut value = BoxValue::alpha() + BoxValue::alpha() * BoxValue::alpha();
+        assert!(value.insert(BoxValue::from(1), 5_u32));
+        assert_eq!(
+            value,
+            6_u32 * BoxValue::alpha() + BoxValue::alpha() * BoxValue::alpha()
+        );
+        // the public constructors do not produce zero multiplicities
+        let wrapped = BoxValue::alpha().wrap::<AnyBox>(0_u32);
+        wrapped.assert_normalized();
+        assert!(wrapped.is_empty());
+        let from_vec = BoxValue::from(vec![
+            BoxValue::zero().cast::<AnyBox>(),
+            BoxValue::anti_zero().cast(),
+        ]);
+        from_vec.assert_normalized();
+        assert!(from_vec.is_empty());
+
+        // a term cancels against an equal child of the opposite color
+        let mut value = BoxValue::from(3);
+        assert!(value.insert(BoxValue::anti_zero(), 1_u32));
+        value.assert_normalized();
+        assert_eq!(value, BoxValue::from(2));
+        assert!(value.insert(BoxValue::anti_zero(), 5_u32));
+        assert_eq!(value, BoxValue::from(-3));
+        assert!(value.insert(BoxValue::zero(), 3_u32));
+        assert_eq!(value, BoxValue::from(0));
+    }
+
+    #[test]
+    fn test_box_at_path() {
+        let alpha = BoxValue::alpha().cast::<AnyBox>();
+        let poly = (BoxValue::from(2) + 3_u32 * BoxValue::alpha()).cast::<AnyBox>();
+        let mut nested = BoxValue::empty();
+        nested.extend_with_mul(poly.clone(), 4_u32);
+        nested.extend(alpha.clone());
+
+        let (found, mul) = nested.box_at_path(&[&poly]).unwrap();
+        assert_eq!(*mul, 4_u32);
+        assert_eq!(found.lengths, &poly.lengths[..]);
+
+        let one = BoxValue::from(1).cast::<AnyBox>();
+        let (leaf, mul) = nested.box_at_path(&[&poly, &one]).unwrap();
+        assert_eq!(*mul, 3_u32);
+        assert_eq!(leaf.kinds, &[BoxKind::Num, BoxKind::Empty]);
+
+        let (_, mul) = nested.box_at_path(&[&alpha, &one]).unwrap();
+        assert_eq!(*mul, 1_u32);
+
+        let two = BoxValue::from(2).cast::<AnyBox>();
+        assert!(nested.box_at_path(&[&poly, &two]).is_none());
+        assert!(nested.box_at_path(&[&two]).is_none());
+        assert!(nested.box_at_path::<AnyBox>(&[]).is_some());
+    }
+
+    #[test]
+    fn test_retain_terms() {
+        let alpha = BoxValue::alpha();
+        let alpha_sq = alpha.clone().pow(2);
+        let poly = BoxValue::from(2) + 3_u32 * alpha + 4_u32 * alpha_sq.clone();
+
+        let even = poly
+            .clone()
+            .retain_terms(|_, mul| mul % Natural::from(2_u32) == 0);
+        assert_eq!(even, BoxValue::from(2) + 4_u32 * alpha_sq);
+
+        let low = poly.retain_terms(|term, _| term.try_to_u32().is_some_and(|k| k < 1));
+        assert_eq!(low, BoxValue::from(2).cast());
+        assert_eq!(low.get_kind(0), BoxKind::Num);
+
+        let anti = BoxValue::from(5).into_anti().retain_terms(|_, _| true);
+        assert_eq!(anti, BoxValue::from(5).into_anti());
+    }
+
+    #[test]
+    fn test_ord() {
+        let poly = BoxValue::from(2) + BoxValue::alpha() * BoxValue::alpha();
+        let boxes = vec![
+            BoxValue::zero().cast::<AnyBox>(),
+            BoxValue::anti_zero().cast(),
+            BoxValue::from(1).cast(),
+            BoxValue::from(-1).cast(),
+            BoxValue::from(3).cast(),
+            BoxValue::from(3).cast(),
+            BoxValue::alpha().cast(),
+            BoxValue::anti_alpha().cast(),
+            poly.clone().cast(),
+            (poly.clone() * BoxValue::alpha()).cast(),
+            BoxValue::beta(2_u32).cast(),
+            BoxValue::from(3).wrap::<AnyBox>(2_u32),
+        ];
+
+        for a in &boxes {
+            for b in &boxes {
+                assert_eq!(a == b, a.cmp(b) == Ordering::Equal);
+                assert_eq!(a.cmp(b), b.cmp(a).reverse());
+                for c in &boxes {
+                    if a <= b && b <= c {
+                        assert!(a <= c);
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::BoxVariant;
+
+        let boxes = vec![
+            BoxVariant::zero(),
+            BoxVariant::anti_zero(),
+            BoxVariant::from(-3),
+            BoxVariant::from(1) + BoxVariant::anti_alpha() * BoxVariant::alpha(),
+            BoxVariant::from(2)
+                .into_anti()
+                .wrap::<AnyBox>(3_u32)
+                .wrap::<AnyBox>(1_u32)
+                .into_anti(),
+        ];
+
+        for value in boxes {
+            let json = serde_json::to_string(&value).unwrap();
+            let back: BoxVariant = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, value);
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    process,
+};
+
+use box_algebra::{
+    BoxValue, BoxVariant,
+    error::BoxAlgebraError,
+    parser::{EvalError, evaluate_expr_in, parse_expr},
+    store::BoxStore,
+};
+
+/// Rendering of the results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Format {
+    /// The bracket notation of `Display`
+    #[default]
+    Text,
+    /// LaTeX, see `BoxValue::to_latex`
+    Latex,
+    /// A Graphviz DOT digraph, see `BoxValue::to_dot`
+    Dot,
+    /// JSON, only available with the `serde` feature
+    Json,
+}
+
+impl Format {
+    /// Parse the value of the `--format` flag
+    fn parse(value: &str) -> Result<Format, &'static str> {
+        match value {
+            "text" => Ok(Format::Text),
+            "latex" => Ok(Format::Latex),
+            "dot" => Ok(Format::Dot),
+            "json" if cfg!(feature = "serde") => Ok(Format::Json),
+            "json" => Err("json output requires the serde feature"),
+            _ => Err("unknown format, expected text, latex, dot or json"),
+        }
+    }
+
+    /// Render a result in this format
+    fn render(self, value: &BoxVariant) -> Result<String, BoxAlgebraError> {
+        Ok(match self {
+            Format::Text => value.to_string(),
+            Format::Latex => value.clone().into_any_raw().to_latex(),
+            Format::Dot => value.clone().into_any_raw().to_dot(),
+            #[cfg(feature = "serde")]
+            Format::Json => serde_json::to_string(value)?,
+            #[cfg(not(feature = "serde"))]
+            Format::Json => unreachable!("json is rejected when parsing the format"),
+        })
+    }
+}
+
+/// Configuration of the evaluator
+#[derive(Debug, PartialEq)]
+struct Config {
+    /// File with one expression per line
+    input: String,
+    /// File to write the results to instead of stdout
+    output: Option<String>,
+    /// Only parse the expressions and report syntax errors
+    check: bool,
+    /// Rendering of the results
+    format: Format,
+}
+
+impl Config {
+    /// Build the configuration from the command line arguments
+    fn build(mut args: impl Iterator<Item = String>) -> Result<Config, &'static str> {
+        args.next();
+
+        let mut input = None;
+        let mut output = None;
+        let mut check = false;
+        let mut format = Format::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--output" | "-o" => {
+                    output = Some(args.next().ok_or("missing path after --output")?);
+                }
+                "--check" => check = true,
+                "--format" | "-f" => {
+                    format = Format::parse(&args.next().ok_or("missing value after --format")?)?;
+                }
+                _ if input.is_none() => input = Some(arg),
+                _ => return Err("unexpected argument"),
+            }
+        }
+
+        let input = input.ok_or("missing input file")?;
+        Ok(Config {
+            input,
+            output,
+            check,
+            format,
+        })
+    }
+}
+
+/// Evaluate every non-empty line read from `input` and write one result per line to `out`
+///
+/// The input is read one line at a time, so only a single expression is held in memory.
+fn evaluate_stream(
+    input: impl BufRead,
+    out: &mut impl Write,
+    store: &BoxStore,
+    format: Format,
+) -> Result<(), BoxAlgebraError> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value = evaluate_expr_in(&line, store)?;
+        writeln!(out, "{}", format.render(&value)?)?;
+    }
+    Ok(())
+}
+
+/// Parse every non-empty line read from `input` and report syntax errors to `out`
+///
+/// Every error is reported with its line and column, both counting from one. Returns the
+/// number of lines that failed to parse.
+fn check_stream(input: impl BufRead, out: &mut impl Write) -> Result<usize, BoxAlgebraError> {
+    let mut failures = 0;
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Err(err) = parse_expr(&line) {
+            let start = match &err {
+                EvalError::Lex { span } | EvalError::Parse { span, .. } => span.start,
+                _ => 0,
+            };
+            let column = line[..start].chars().count() + 1;
+            writeln!(out, "line {}, column {}: {}", number + 1, column, err)?;
+            failures += 1;
+        }
+    }
+    Ok(failures)
+}
+
+/// Evaluate every non-empty line of the input file and write one result per line
+///
+/// With `--check` the lines are only parsed, and an error is returned if any line fails.
+fn run(config: Config) -> Result<(), BoxAlgebraError> {
+    let input = BufReader::new(File::open(&config.input)?);
+
+    if config.check {
+        let failures = check_stream(input, &mut io::stderr())?;
+        if failures > 0 {
+            return Err(BoxAlgebraError::Check { failures });
+        }
+        return Ok(());
+    }
+
+    let mut store = BoxStore::new();
+    store.store_box_with_name("alpha", BoxValue::alpha());
+
+    let mut out: Box<dyn Write> = match &config.output {
+        Some(path) => {
+            colored::control::set_override(false);
+            Box::new(File::create(path)?)
+        }
+        None => Box::new(io::stdout()),
+    };
+
+    evaluate_stream(input, &mut out, &store, config.format)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+fn main() {
+    let result = Config::build(env::args())
+        .map_err(BoxAlgebraError::Config)
+        .and_then(run);
+
+    if let Err(e) = result {
+        eprintln!("Application error: {e}");
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{env, fs, path::PathBuf, process};
+
+    use box_algebra::{
+        BoxValue, error::BoxAlgebraError, parser::evaluate_expr_in, store::BoxStore,
+    };
+
+    use crate::{Config, Format, check_stream, evaluate_stream, run};
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Return a path in the temporary directory that only this process uses
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("box_algebra_{}_{}", process::id(), name))
+    }
+
+    #[test]
+    fn test_build() {
+        let config = Config::build(args(&["box", "input.txt", "--output", "out.txt"]));
+        let exp = Config {
+            input: "input.txt".to_string(),
+            output: Some("out.txt".to_string()),
+            check: false,
+            format: Format::Text,
+        };
+        assert_eq!(config, Ok(exp));
+
+        let config = Config::build(args(&["box", "--check", "input.txt"])).unwrap();
+        assert!(config.check);
+
+        assert!(Config::build(args(&["box"])).is_err());
+        assert!(Config::build(args(&["box", "input.txt", "--output"])).is_err());
+    }
+
+    #[test]
+    fn test_run_output() {
+        let input = temp_path("test_run_output_input.txt");
+        let output = temp_path("test_run_output_result.txt");
+        fs::write(&input, "2 + 3\n\nalpha - alpha\n2 * 3 + 1\n").unwrap();
+
+        let config = Config {
+            input: input.to_string_lossy().into_owned(),
+            output: Some(output.to_string_lossy().into_owned()),
+            check: false,
+            format: Format::Text,
+        };
+        run(config).unwrap();
+
+        let result = fs::read_to_string(&output).unwrap();
+        assert_eq!(result, "5\n0\n7\n");
+
+        fs::remove_file(input).unwrap();
+        fs::remove_file(output).unwrap();
+    }
+
+    #[test]
+    fn test_evaluate_stream() {
+        let mut store = BoxStore::new();
+        store.store_box_with_name("alpha", BoxValue::alpha());
+
+        let mut contents = String::new();
+        for i in 0..500 {
+            contents.push_str(&format!("{} * alpha + {} * (alpha + 1)\n", i, i % 7));
+            if i % 100 == 0 {
+                contents.push('\n');
+            }
+        }
+
+        let mut batch = String::new();
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let value = evaluate_expr_in(line, &store).unwrap();
+            batch.push_str(&format!("{}\n", value));
+        }
+
+        let mut streamed = Vec::new();
+        evaluate_stream(contents.as_bytes(), &mut streamed, &store, Format::Text).unwrap();
+        assert_eq!(String::from_utf8(streamed).unwrap(), batch);
+    }
+
+    #[test]
+    fn test_check() {
+        let contents = "2 + 3\n\nalpha * (2 +\n1 + 2 $ 3\n2 * alpha\n";
+        let mut report = Vec::new();
+        let failures = check_stream(contents.as_bytes(), &mut report).unwrap();
+        assert_eq!(failures, 2);
+
+        let report = String::from_utf8(report).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("line 3, column 13: parse error"));
+        assert!(lines[1].starts_with("line 4, column 7: lexer error"));
+
+        let mut report = Vec::new();
+        let failures = check_stream("2 + 3\nalpha\n".as_bytes(), &mut report).unwrap();
+        assert_eq!(failures, 0);
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_run_check() {
+        let input = temp_path("test_run_check_input.txt");
+        fs::write(&input, "2 + 3\nalpha * (2 +\n").unwrap();
+        let config = Config {
+            input: input.to_string_lossy().into_owned(),
+            output: None,
+            check: true,
+            format: Format::Text,
+        };
+        assert!(matches!(
+            run(config),
+            Err(BoxAlgebraError::Check { failures: 1 })
+        ));
+
+        fs::write(&input, "2 + 3\nalpha\n").unwrap();
+        let config = Config {
+            input: input.to_string_lossy().into_owned(),
+            output: None,
+            check: true,
+            format: Format::Text,
+        };
+        assert!(run(config).is_ok());
+        fs::remove_file(input).unwrap();
+    }
+
+    #[test]
+    fn test_format() {
+        let config = Config::build(args(&["box", "input.txt", "--format", "latex"])).unwrap();
+        assert_eq!(config.format, Format::Latex);
+        let config = Config::build(args(&["box", "input.txt"])).unwrap();
+        assert_eq!(config.format, Format::Text);
+        assert_eq!(
+            Config::build(args(&["box", "input.txt", "--format", "pdf"])),
+            Err("unknown format, expected text, latex, dot or json")
+        );
+        assert!(Config::build(args(&["box", "input.txt", "--format"])).is_err());
+
+        let mut store = BoxStore::new();
+        store.store_box_with_name("alpha", BoxValue::alpha());
+        let value = evaluate_expr_in("3 * alpha^2 + 6", &store).unwrap();
+        let render = |format: Format| {
+            let mut out = Vec::new();
+            evaluate_stream("3 * alpha^2 + 6".as_bytes(), &mut out, &store, format).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(render(Format::Text), format!("{}\n", value));
+        assert_eq!(render(Format::Latex), "3\\alpha^{2} + 6\n");
+        let dot = render(Format::Dot);
+        assert!(dot.starts_with("digraph box {\n"));
+        assert_eq!(dot, format!("{}\n", value.into_any_raw().to_dot()));
+
+        #[cfg(feature = "serde")]
+        {
+            let json = render(Format::Json);
+            let back: box_algebra::BoxVariant = serde_json::from_str(json.trim()).unwrap();
+            assert_eq!(back, evaluate_expr_in("3 * alpha^2 + 6", &store).unwrap());
+        }
+        #[cfg(not(feature = "serde"))]
+        assert!(Format::parse("json").is_err());
+    }
+}
@@ -237,6 +237,41 @@ impl BoxValue<MaxelBox> {
     }
 }
 
+impl<T: BoxType> BoxValue<T> {
+    /// Outer product of two boxes
+    ///
+    /// Unlike box multiplication, which adds the child boxes pairwise, the outer product keeps
+    /// both child boxes apart: every pair of child boxes `a` and `b` becomes the pixel `⌈a, b⌉`
+    /// whose multiplicity is the product of their multiplicities. The colors of `a` and `b`
+    /// move to the pixel and combine like the colors in box multiplication, and so do the
+    /// colors of the two roots.
+    pub fn tensor<U: BoxType>(self, other: BoxValue<U>) -> BoxValue<MaxelBox> {
+        let mut result = BoxValue::<MaxelBox>::new();
+        result.kinds.push(BoxKind::Maxel);
+        result.colors.push(self.get_color(0) * other.get_color(0));
+        result.multiplicities.push(Natural::from(1_u32));
+        result.lengths.push(1);
+
+        let right: Vec<(BoxValue<AnyBox>, Natural)> = other.into_terms().collect();
+        for (mut x, x_mul) in self.into_terms() {
+            let x_col = x.get_color(0);
+            x.set_color(0, Color::Black);
+            for (y, y_mul) in &right {
+                let mut y = y.clone();
+                let y_col = y.get_color(0);
+                y.set_color(0, Color::Black);
+
+                let mut pixel = BoxValue::pixel(x.clone(), y);
+                pixel.set_color(0, x_col * y_col);
+                result.extend_with_mul(pixel, x_mul.clone() * y_mul);
+            }
+        }
+
+        result.sort_immediate_children();
+        result
+    }
+}
+
 #[macro_export]
 macro_rules! pixel {
     ($x:expr, $y:expr) => {{ $crate::BoxValue::<$crate::maxel::PixelBox>::pixel($x.into(), $y.into()) }};
@@ -346,7 +381,7 @@ macro_rules! maxel {
 
 #[cfg(test)]
 mod tests {
-    use crate::BoxValue;
+    use crate::{BoxValue, BoxVariant, Color, MaxelBox};
 
     #[test]
     fn test_pixel() {
@@ -390,4 +425,31 @@ mod tests {
         let expected = vexel!([1, 1, 2, 3]);
         assert_eq!(prod, expected);
     }
+
+    #[test]
+    fn test_tensor() {
+        let alpha = BoxValue::alpha();
+        assert_eq!(alpha.clone() * alpha.clone(), alpha.clone().pow(2));
+        let outer = BoxVariant::from(alpha.clone().tensor(alpha.clone()));
+        assert_eq!(outer, maxel![[[1, 1]]]);
+
+        let poly = BoxValue::from(1) + 2_u32 * alpha.clone();
+        let outer = poly.tensor(BoxValue::from(-3));
+        let mut constant = pixel!(0, 0);
+        constant.set_multiplicity(0, 3_u32);
+        constant.set_color(0, Color::Red);
+        let mut linear = pixel!(1, 0);
+        linear.set_multiplicity(0, 6_u32);
+        linear.set_color(0, Color::Red);
+        let mut exp = BoxValue::<MaxelBox>::from(vec![constant, linear]);
+        exp.sort_immediate_children();
+        assert_eq!(outer, exp);
+
+        let outer = BoxValue::anti_alpha().tensor(BoxValue::anti_alpha());
+        assert_eq!(outer.get_color(1), Color::Black);
+        let outer = BoxValue::anti_alpha().tensor(BoxValue::alpha());
+        assert_eq!(outer.get_color(1), Color::Red);
+        let outer = BoxValue::one().into_anti().tensor(BoxValue::alpha());
+        assert!(outer.is_anti());
+    }
 }
@@ -1,9 +1,53 @@
-use std::ops::Mul;
-
-use malachite::{Natural, base::num::arithmetic::traits::SaturatingSub};
+use std::{
+    fmt::{self, Display, Formatter},
+    iter::Product,
+    ops::Mul,
+};
+
+use malachite::Natural;
+#[cfg(not(feature = "rayon"))]
+use malachite::base::num::arithmetic::traits::SaturatingSub;
 use rapidhash::RapidHashMap;
 
-use crate::{AnyBox, BoxType, BoxValue, BoxVariant, Color, MultinumBox, NumBox, PolynumBox};
+use crate::{
+    AnyBox, BoxKind, BoxType, BoxValue, BoxVariant, Color, MultinumBox, NumBox, PolynumBox,
+    add::{add_boxes, merge_child},
+};
+
+/// Error returned when a multiplicity exceeds the range of `u32`
+///
+/// Multiplicities are arbitrary precision and never overflow by themselves, the checked
+/// operations bound them to `u32` for callers that need to store them in fixed width.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverflowError {
+    /// The first multiplicity that does not fit into `u32`
+    pub multiplicity: Natural,
+}
+
+impl Display for OverflowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "multiplicity {} exceeds u32::MAX", self.multiplicity)
+    }
+}
+
+impl std::error::Error for OverflowError {}
+
+/// Error returned when the kinds of the operands of an arithmetic operation do not fit together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMismatchError {
+    /// The operation, e.g. `"add"` or `"multiply"`
+    pub op: &'static str,
+    pub left: BoxKind,
+    pub right: BoxKind,
+}
+
+impl Display for TypeMismatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot {} {:?} and {:?}", self.op, self.left, self.right)
+    }
+}
+
+impl std::error::Error for TypeMismatchError {}
 
 /// Trait for the output type of box multiplication
 pub trait BoxMul<Rhs = Self> {
@@ -35,7 +79,6 @@ impl<L: BoxType + BoxMul<R>, R: BoxType> Mul<BoxValue<R>> for BoxValue<L> {
     /// Multiply two boxes
     fn mul(self, rhs: BoxValue<R>) -> Self::Output {
         let mut result = BoxValue::new();
-        let mut unique_children: RapidHashMap<u64, BoxValue<AnyBox>> = RapidHashMap::default();
 
         let lhs_col = self.get_color(0);
         let rhs_col = rhs.get_color(0);
@@ -43,13 +86,20 @@ impl<L: BoxType + BoxMul<R>, R: BoxType> Mul<BoxValue<R>> for BoxValue<L> {
         let lhs_kind = self.get_kind(0);
         let rhs_kind = rhs.get_kind(0);
 
+        #[cfg(feature = "rayon")]
+        let unique_children = par_mul_children(self, rhs.cast());
+
+        #[cfg(not(feature = "rayon"))]
+        let mut unique_children: RapidHashMap<u64, BoxValue<AnyBox>> = RapidHashMap::default();
+
+        #[cfg(not(feature = "rayon"))]
         for left_child in self {
             for right_child in rhs.clone() {
                 let left_mul = left_child.get_multiplicity(0);
                 let right_mul = right_child.get_multiplicity(0);
                 let mul = left_mul * right_mul;
 
-                let mut box_sum = left_child.clone() + right_child;
+                let mut box_sum = add_boxes(left_child.clone(), right_child, false);
 
                 let col = box_sum.get_color(0);
                 let struct_hash = box_sum.hash_content(unique_children.hasher());
@@ -95,6 +145,38 @@ impl<L: BoxType + BoxMul<R>, R: BoxType> Mul<BoxValue<R>> for BoxValue<L> {
     }
 }
 
+/// Multiply the children of two boxes in parallel
+///
+/// The children of the left box are distributed across threads. Every thread collects the
+/// products into its own map, and the partial maps are merged by accumulating multiplicities,
+/// so the result agrees with the serial multiplication once the children are sorted.
+#[cfg(feature = "rayon")]
+fn par_mul_children<L: BoxType>(
+    lhs: BoxValue<L>,
+    rhs: BoxValue<AnyBox>,
+) -> RapidHashMap<u64, BoxValue<AnyBox>> {
+    use rayon::prelude::*;
+
+    let left_children: Vec<BoxValue<AnyBox>> = lhs.into_iter().collect();
+    left_children
+        .into_par_iter()
+        .fold(RapidHashMap::default, |mut unique_children, left_child| {
+            for right_child in rhs.clone() {
+                let mul = left_child.get_multiplicity(0) * right_child.get_multiplicity(0);
+                let mut box_sum = add_boxes(left_child.clone(), right_child, false);
+                box_sum.set_multiplicity(0, mul);
+                merge_child(&mut unique_children, box_sum);
+            }
+            unique_children
+        })
+        .reduce(RapidHashMap::default, |mut unique_children, partial| {
+            for child in partial.into_values() {
+                merge_child(&mut unique_children, child);
+            }
+            unique_children
+        })
+}
+
 impl<L: BoxType + BoxMul<R>, R: BoxType> Mul<&BoxValue<R>> for &BoxValue<L> {
     type Output = BoxValue<L::Output>;
 
@@ -117,6 +199,217 @@ impl<L: BoxType + BoxMul<R>, R: BoxType> Mul<BoxValue<R>> for &BoxValue<L> {
     }
 }
 
+impl<T: BoxType> BoxValue<T> {
+    /// Multiply the multiplicities of the immediate children by a scalar
+    ///
+    /// This agrees with multiplying by the number box of `n` but leaves the child boxes
+    /// untouched. Multiplying by zero returns the zero box.
+    pub fn scalar_mul(mut self, n: impl Into<Natural>) -> Self {
+        let n = n.into();
+        if n == 0 {
+            return BoxValue::zero().cast();
+        }
+
+        let children: Vec<usize> = self.child_indices().collect();
+        for idx in children {
+            self.multiplicities[idx] *= &n;
+        }
+        self
+    }
+
+    /// Return the products of all pairs of immediate children without merging equal terms
+    ///
+    /// Every child of this box is combined with every child of the other box in iteration
+    /// order, as in box multiplication. Each term has multiplicity one and is paired with the
+    /// product of the multiplicities. Merging equal terms gives the children of the product.
+    pub fn distribute<U: BoxType>(self, other: BoxValue<U>) -> Vec<(BoxValue<AnyBox>, Natural)> {
+        let mut terms = Vec::new();
+        for left_child in self {
+            for right_child in other.clone() {
+                let mul = left_child.get_multiplicity(0) * right_child.get_multiplicity(0);
+                let mut term = add_boxes(left_child.clone(), right_child, false);
+                term.set_multiplicity(0, Natural::from(1_u32));
+                terms.push((term, mul));
+            }
+        }
+        terms
+    }
+
+    /// Divide the multiplicities of the immediate children by a scalar
+    ///
+    /// This is the inverse of [`BoxValue::scalar_mul`] and only divides the top-level
+    /// multiplicities, the child boxes are left untouched. Returns `None` if `n` is zero or if
+    /// a multiplicity is not divisible by `n`.
+    pub fn try_divide_scalar(mut self, n: impl Into<Natural>) -> Option<Self> {
+        let n = n.into();
+        if n == 0 {
+            return None;
+        }
+
+        let children: Vec<usize> = self.child_indices().collect();
+        if children
+            .iter()
+            .any(|&idx| &self.multiplicities[idx] % &n != 0)
+        {
+            return None;
+        }
+        for idx in children {
+            self.multiplicities[idx] /= &n;
+        }
+        Some(self)
+    }
+
+    /// Multiply the box by itself
+    ///
+    /// The product of two immediate children does not depend on their order, so every pair of
+    /// distinct children is multiplied once with twice the product of their multiplicities and
+    /// every child once with itself. The result equals `self.clone() * self.clone()`.
+    pub fn square(&self) -> Self {
+        let children: Vec<BoxValue<AnyBox>> = self.clone().into_iter().collect();
+        let mut unique_children: RapidHashMap<u64, BoxValue<AnyBox>> = RapidHashMap::default();
+        for (i, left_child) in children.iter().enumerate() {
+            let left_mul = left_child.get_multiplicity(0);
+
+            let mut diagonal = add_boxes(left_child.clone(), left_child.clone(), false);
+            diagonal.set_multiplicity(0, &left_mul * &left_mul);
+            merge_child(&mut unique_children, diagonal);
+
+            for right_child in &children[i + 1..] {
+                let mul = Natural::from(2_u32) * &left_mul * right_child.get_multiplicity(0);
+                let mut term = add_boxes(left_child.clone(), right_child.clone(), false);
+                term.set_multiplicity(0, mul);
+                merge_child(&mut unique_children, term);
+            }
+        }
+
+        let mut result = BoxValue::new();
+        result.kinds.push(self.kinds[0] + self.kinds[0]);
+        result.colors.push(self.colors[0] + self.colors[0]);
+        result.multiplicities.push(Natural::from(1_u32));
+        result.lengths.push(1);
+        for child in unique_children.into_values() {
+            if child.get_multiplicity(0) != 0 {
+                result.extend(child);
+            }
+        }
+        result.sort_immediate_children();
+        result
+    }
+
+    /// Raise the box to a power by repeated squaring
+    ///
+    /// The zeroth power is the number box of one. Multiplicities grow without bound, use
+    /// [`BoxValue::checked_pow`] to keep them within `u32`.
+    pub fn pow(self, exp: u32) -> Self {
+        let mut result = BoxValue::one().cast::<T>();
+        let mut base = self;
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.square();
+            }
+        }
+        result
+    }
+
+    /// Raise the box to a power by repeated squaring and check every multiplicity
+    ///
+    /// Returns an error as soon as an intermediate product holds a multiplicity that does not
+    /// fit into `u32`, even if it would cancel out later.
+    pub fn checked_pow(self, exp: u32) -> Result<Self, OverflowError> {
+        let mut result = BoxValue::one().cast::<T>();
+        let mut base = self;
+        let mut exp = exp;
+        base.check_u32()?;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * &base;
+                result.check_u32()?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.square();
+                base.check_u32()?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Raise the box to a power and reduce the coefficients modulo `modulus`
+    ///
+    /// The result is reduced with [`BoxValue::reduce_mod`] after every multiplication of the
+    /// repeated squaring, so the coefficients stay below the modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `modulus` is zero.
+    pub fn pow_mod(self, exp: u32, modulus: u32) -> Self {
+        let mut result = BoxValue::one().cast::<T>().reduce_mod(modulus);
+        let mut base = self.reduce_mod(modulus);
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = (result * &base).reduce_mod(modulus);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.square().reduce_mod(modulus);
+            }
+        }
+        result
+    }
+
+    /// Reduce the coefficients modulo `n` to their representatives in `0..n`
+    ///
+    /// The box is brought into its canonical form first, so every term occurs once with its
+    /// signed coefficient, i.e. the multiplicity of an immediate child box with the sign of its
+    /// color. The coefficient is replaced by its remainder in `0..n` and terms with remainder
+    /// zero are dropped, so an anti-box term with multiplicity `c` becomes a box term with
+    /// multiplicity `n - c % n`. Nested multiplicities like exponents are not reduced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn reduce_mod(self, n: u32) -> Self {
+        assert!(n != 0, "modulus must not be zero");
+        let modulus = Natural::from(n);
+        let value = self.canonical();
+
+        let mut result = BoxValue::new();
+        result.kinds.push(value.kinds[0]);
+        result.colors.push(value.colors[0]);
+        result.multiplicities.push(value.multiplicities[0].clone());
+        result.lengths.push(1);
+
+        for (term, mul) in value.into_terms() {
+            let mut mul = mul % &modulus;
+            if mul != 0 && term.is_anti() {
+                mul = &modulus - mul;
+            }
+            if mul != 0 {
+                result.extend_with_mul(term.as_box(), mul);
+            }
+        }
+        result.update_number_kind();
+        result.sort_immediate_children();
+        result
+    }
+
+    /// Check that all multiplicities fit into `u32`
+    pub(crate) fn check_u32(&self) -> Result<(), OverflowError> {
+        match self.multiplicities.iter().find(|&mul| *mul > u32::MAX) {
+            Some(mul) => Err(OverflowError {
+                multiplicity: mul.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
 impl<T: BoxType + BoxMul<T>> Mul<BoxValue<T>> for u32 {
     type Output = BoxValue<T::Output>;
 
@@ -189,11 +482,24 @@ impl<T: BoxType + BoxMul<T>> Mul<i64> for BoxValue<T> {
     }
 }
 
-impl Mul for BoxVariant {
-    type Output = Self;
+impl<T: BoxType + BoxMul<T, Output = T>> Product for BoxValue<T> {
+    /// Multiply the boxes starting from the number box of one
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(BoxValue::one().cast(), |acc, value| acc * value)
+    }
+}
 
-    fn mul(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
+impl Product for BoxVariant {
+    /// Multiply the boxes starting from the number box of one
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(BoxVariant::one(), |acc, value| acc * value)
+    }
+}
+
+impl BoxVariant {
+    /// Multiply two boxes, or return an error if their kinds cannot be multiplied
+    pub fn try_mul(self, rhs: Self) -> Result<Self, TypeMismatchError> {
+        let value = match (self, rhs) {
             (BoxVariant::Empty(l), r) => {
                 let l_col = l.get_color(0);
                 let r_col = r.get_color(0);
@@ -225,8 +531,49 @@ impl Mul for BoxVariant {
             (BoxVariant::Maxel(l), BoxVariant::Maxel(r)) => {
                 BoxVariant::repack_raw(BoxValue::mul_max(l, r))
             }
-            (l, r) => panic!("Type Error: Cannot multiply {:?} with {:?}", l, r),
+            (l, r) => {
+                return Err(TypeMismatchError {
+                    op: "multiply",
+                    left: BoxKind::from(&l),
+                    right: BoxKind::from(&r),
+                });
+            }
+        };
+        Ok(value)
+    }
+
+    /// Raise the box to a power by repeated squaring, or return an error if the box cannot be
+    /// multiplied with itself
+    ///
+    /// The zeroth power is the number box of one.
+    pub fn try_pow(self, exp: u32) -> Result<Self, TypeMismatchError> {
+        let mut result = BoxVariant::one();
+        let mut base = self;
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.try_mul(base.clone())?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.clone().try_mul(base)?;
+            }
         }
+        Ok(result)
+    }
+}
+
+impl Mul for BoxVariant {
+    type Output = Self;
+
+    /// Multiply two boxes
+    ///
+    /// # Panics
+    /// Panics if the kinds of the boxes cannot be multiplied, use [`BoxVariant::try_mul`] to get an
+    /// error instead.
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.try_mul(rhs)
+            .unwrap_or_else(|err| panic!("Type Error: {}", err))
     }
 }
 
@@ -305,7 +652,9 @@ impl Mul<i64> for BoxVariant {
 #[cfg(test)]
 mod tests {
 
-    use crate::*;
+    use malachite::{Integer, Natural};
+
+    use crate::{builder::BoxBuilder, poly::Poly, *};
 
     #[test]
     fn test_mul() {
@@ -335,4 +684,222 @@ mod tests {
         let expected = BoxVariant::from(1) + (-1) * BoxVariant::alpha() * BoxVariant::alpha();
         assert_eq!(prod, expected);
     }
+
+    #[test]
+    fn test_mul_normalized() {
+        // every product below produces colliding terms that must be merged, not overwritten
+        let binom = BoxValue::from(1) + BoxValue::alpha();
+        let square = binom.clone() * binom.clone();
+        square.assert_normalized();
+        assert_eq!(
+            square.multiplicity_of(&BoxValue::from(1)),
+            Natural::from(2_u32)
+        );
+
+        let diff = BoxValue::from(1) + BoxValue::from(-1) * BoxValue::alpha();
+        let prod = binom.clone() * diff.clone();
+        prod.assert_normalized();
+        assert_eq!(
+            prod,
+            BoxValue::from(1) + BoxValue::from(-1) * BoxValue::alpha().pow(2)
+        );
+
+        let cube = square * binom;
+        cube.assert_normalized();
+        assert_eq!(
+            cube.multiplicity_of(&BoxValue::from(2)),
+            Natural::from(3_u32)
+        );
+
+        let mixed = (diff.clone() * diff).pow(2) + BoxValue::from(-1);
+        mixed.assert_normalized();
+    }
+
+    #[test]
+    fn test_scalar_mul() {
+        assert_eq!(BoxValue::from(3).scalar_mul(4_u32), BoxValue::from(12));
+
+        let poly = BoxValue::from(-2) + 3_u32 * BoxValue::alpha() * BoxValue::alpha();
+        assert_eq!(poly.clone().scalar_mul(5_u32), 5_u32 * poly.clone());
+
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(poly.scalar_mul(0_u32), zero);
+    }
+
+    #[test]
+    fn test_distribute() {
+        let binom = BoxValue::from(1) + BoxValue::alpha();
+        let terms = binom.clone().distribute(binom.clone());
+
+        let one = BoxValue::zero().cast::<AnyBox>();
+        let alpha = BoxValue::from(1).cast::<AnyBox>();
+        let alpha_sq = BoxValue::from(2).cast::<AnyBox>();
+        let exp = vec![
+            (one, Natural::from(1_u32)),
+            (alpha.clone(), Natural::from(1_u32)),
+            (alpha, Natural::from(1_u32)),
+            (alpha_sq, Natural::from(1_u32)),
+        ];
+        assert_eq!(terms, exp);
+
+        let merged = terms
+            .into_iter()
+            .fold(BoxBuilder::<PolynumBox>::new(), |builder, (term, mul)| {
+                builder.add_term(term, mul)
+            })
+            .build();
+        assert_eq!(merged, binom.clone() * binom);
+    }
+
+    #[test]
+    fn test_try_divide_scalar() {
+        assert_eq!(
+            BoxValue::from(12).try_divide_scalar(4_u32),
+            Some(BoxValue::from(3))
+        );
+        assert_eq!(BoxValue::from(7).try_divide_scalar(2_u32), None);
+        assert_eq!(BoxValue::from(7).try_divide_scalar(0_u32), None);
+
+        // the exponent of alpha^2 is a nested multiplicity and stays untouched
+        let poly = BoxValue::from(-4) + 6_u32 * BoxValue::alpha().pow(2);
+        let exp = BoxValue::from(-2) + 3_u32 * BoxValue::alpha().pow(2);
+        assert_eq!(poly.clone().try_divide_scalar(2_u32), Some(exp));
+        assert_eq!(poly.try_divide_scalar(4_u32), None);
+    }
+
+    #[test]
+    fn test_pow() {
+        let alpha = BoxValue::alpha();
+        assert_eq!(
+            alpha.clone().pow(3),
+            alpha.clone() * alpha.clone() * alpha.clone()
+        );
+
+        let binom = BoxValue::from(1) + alpha.clone();
+        assert_eq!(
+            binom.clone().pow(2),
+            BoxValue::from(1) + 2_u32 * alpha.clone() + alpha.clone() * alpha.clone()
+        );
+        assert_eq!(BoxValue::from(3).pow(4), BoxValue::from(81));
+        assert_eq!(binom.pow(0), BoxValue::from(1).cast());
+    }
+
+    #[test]
+    fn test_mul_beyond_u64() {
+        let big = Natural::from(u64::MAX);
+        let prod = BoxValue::from(u64::MAX) * BoxValue::from(u64::MAX);
+        assert_eq!(prod.get_multiplicity(1), &big * &big);
+
+        let binom = BoxValue::from(u64::MAX) + BoxValue::alpha();
+        let square = binom.pow(2).annihilate();
+        let coeffs = square.to_coefficients().unwrap();
+        let exp: Vec<Integer> = vec![
+            Integer::from(&big * &big),
+            Integer::from(Natural::from(2_u32) * &big),
+            Integer::from(1),
+        ];
+        assert_eq!(coeffs, exp);
+    }
+
+    #[test]
+    fn test_product() {
+        let binom = BoxValue::from(1) + BoxValue::alpha();
+        let values = vec![BoxValue::from(3).cast::<PolynumBox>(), binom.clone()];
+        for value in values.clone() {
+            let prod = BoxValue::one().cast::<PolynumBox>() * value.clone();
+            assert!(prod.reduces_to(&value));
+        }
+
+        let prod: BoxValue<PolynumBox> = values.into_iter().product();
+        assert_eq!(prod, BoxValue::from(3) * binom.clone());
+        assert_eq!(
+            vec![binom.clone(); 3].into_iter().product::<BoxValue<_>>(),
+            binom.pow(3)
+        );
+
+        let prod: BoxVariant = (1..=4).map(BoxVariant::from).product();
+        assert_eq!(prod, BoxVariant::from(24));
+    }
+
+    #[test]
+    fn test_square() {
+        let alpha = BoxValue::alpha();
+        let values = [
+            BoxValue::from(7).cast::<PolynumBox>(),
+            BoxValue::from(-3).cast(),
+            BoxValue::from(1) + BoxValue::from(-2) * &alpha + 5_u32 * alpha.clone().pow(3),
+            (BoxValue::from(2) + &alpha).pow(5),
+            (BoxValue::from(1) + &alpha).into_anti(),
+            BoxValue::zero().cast(),
+        ];
+        for value in values {
+            assert_eq!(value.square(), value.clone() * value.clone());
+        }
+    }
+
+    #[test]
+    fn test_pow_mod() {
+        let alpha = BoxValue::alpha();
+        let bases = [
+            BoxValue::from(3).cast::<PolynumBox>(),
+            BoxValue::from(1) + BoxValue::from(2) * &alpha,
+            BoxValue::from(2) + &alpha + alpha.clone().pow(2),
+        ];
+        // the exponents of the results stay below the moduli, so only coefficients are reduced
+        for base in bases {
+            for exp in [0, 1, 2, 3] {
+                for modulus in [7_u32, 11, 13] {
+                    let exp_value = base
+                        .clone()
+                        .pow(exp)
+                        .map_multiplicities(&|c| c % Natural::from(modulus));
+                    assert_eq!(base.clone().pow_mod(exp, modulus), exp_value);
+                }
+            }
+        }
+
+        assert_eq!(
+            BoxValue::from(2).pow_mod(10, 1),
+            BoxValue::zero().cast::<NumBox>()
+        );
+    }
+
+    #[test]
+    fn test_reduce_mod() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(6) + BoxValue::from(5) * &alpha;
+        assert_eq!(poly.clone().reduce_mod(5), BoxValue::from(1).cast());
+
+        // negative coefficients wrap around to their representative
+        let poly = BoxValue::from(-2) + BoxValue::from(13) * &alpha + alpha.clone().pow(9);
+        let exp = BoxValue::from(5) + BoxValue::from(6) * &alpha + alpha.clone().pow(9);
+        assert_eq!(poly.clone().reduce_mod(7), exp);
+        assert_eq!(
+            poly.clone().into_anti().reduce_mod(7),
+            BoxValue::from(2) + &alpha + BoxValue::from(6) * alpha.clone().pow(9)
+        );
+
+        assert_eq!(poly.reduce_mod(1), BoxValue::zero().cast());
+        assert_eq!(BoxValue::from(-10).reduce_mod(5), BoxValue::zero().cast());
+    }
+
+    #[test]
+    fn test_checked_pow() {
+        let binom = BoxValue::from(1) + BoxValue::alpha();
+        assert_eq!(binom.clone().checked_pow(20), Ok(binom.clone().pow(20)));
+
+        let err = binom.checked_pow(40).unwrap_err();
+        assert!(err.multiplicity > u32::MAX);
+        assert!(BoxValue::from(u64::MAX).checked_pow(1).is_err());
+    }
+
+    #[test]
+    fn test_mul_large() {
+        let left: Vec<i64> = (0..200).map(|i| (i * 7) % 11 - 5).collect();
+        let right: Vec<i64> = (0..150).map(|i| (i * 5) % 13 - 6).collect();
+        let prod = BoxValue::from_sequence(&left) * BoxValue::from_sequence(&right);
+
+        let exp = Poly::new(left) * Poly::new(right);
+        assert_eq!(prod, exp.to_box());
+    }
 }
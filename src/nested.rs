@@ -0,0 +1,99 @@
+//! Plain nested data representation of boxes for interchange with other tools
+
+use malachite::Natural;
+
+use crate::{BoxKind, BoxType, BoxValue, Color};
+
+/// A box as a recursive list of its child boxes and their multiplicities
+///
+/// This is a plain data shape that is easy to construct by hand and does not depend on the
+/// flat storage layout of [`BoxValue`]. The multiplicity of the root is not represented.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NestedTerm {
+    /// A box of the given kind
+    Boxed(BoxKind, Vec<(NestedTerm, Natural)>),
+    /// An anti-box of the given kind
+    Anti(BoxKind, Vec<(NestedTerm, Natural)>),
+}
+
+impl<T: BoxType> BoxValue<T> {
+    /// Construct a box from its nested representation
+    ///
+    /// The children are stored in the given order and are neither merged nor sorted, the root
+    /// gets multiplicity one.
+    pub fn from_nested(term: NestedTerm) -> Self {
+        let (kind, color, children) = match term {
+            NestedTerm::Boxed(kind, children) => (kind, Color::Black, children),
+            NestedTerm::Anti(kind, children) => (kind, Color::Red, children),
+        };
+
+        let mut result = BoxValue::new();
+        result.kinds.push(kind);
+        result.colors.push(color);
+        result.multiplicities.push(Natural::from(1_u32));
+        result.lengths.push(1);
+        for (child, mul) in children {
+            result.extend_with_mul(BoxValue::<T>::from_nested(child), mul);
+        }
+        result
+    }
+
+    /// Convert the box into its nested representation
+    ///
+    /// The children keep their stored order, so `from_nested` returns the box unchanged as long
+    /// as its root has multiplicity one.
+    pub fn to_nested(&self) -> NestedTerm {
+        self.fold(&mut |kind, color, children| match color {
+            Color::Black => NestedTerm::Boxed(kind, children),
+            Color::Red => NestedTerm::Anti(kind, children),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use malachite::Natural;
+
+    use crate::{AnyBox, BoxKind, BoxValue, nested::NestedTerm};
+
+    fn round_trip(value: BoxValue<AnyBox>) {
+        assert_eq!(BoxValue::from_nested(value.to_nested()), value);
+    }
+
+    #[test]
+    fn test_nested() {
+        assert_eq!(
+            BoxValue::zero().to_nested(),
+            NestedTerm::Boxed(BoxKind::Empty, Vec::new())
+        );
+
+        let empty = NestedTerm::Boxed(BoxKind::Empty, Vec::new());
+        let anti_empty = NestedTerm::Anti(BoxKind::Empty, Vec::new());
+        let exp = NestedTerm::Anti(
+            BoxKind::Num,
+            vec![
+                (empty, Natural::from(2_u32)),
+                (anti_empty, Natural::from(3_u32)),
+            ],
+        );
+        let value = BoxValue::<AnyBox>::from_nested(exp.clone());
+        assert_eq!(value.to_nested(), exp);
+        assert_eq!(value.canonical(), BoxValue::from(1).cast());
+    }
+
+    #[test]
+    fn test_nested_round_trip() {
+        round_trip(BoxValue::empty());
+        round_trip(BoxValue::from(5).cast());
+        round_trip(BoxValue::from(-5).cast());
+
+        let poly = BoxValue::from(2) + 3_u32 * BoxValue::alpha() * BoxValue::alpha();
+        round_trip(poly.clone().cast());
+
+        let mut nested = BoxValue::anti_empty();
+        nested.extend_with_mul(poly.into_anti(), 2_u32);
+        nested.extend(BoxValue::anti_alpha());
+        round_trip(nested.wrap(1_u32));
+    }
+}
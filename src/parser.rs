@@ -1,8 +1,66 @@
-use crate::{BoxValue, BoxVariant, store::BoxStore};
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::Range,
+};
+
+use crate::{
+    AnyBox, BoxKind, BoxValue, BoxVariant, PolynumBox, mul::TypeMismatchError, store::BoxStore,
+};
 
 use chumsky::prelude::*;
 use logos::{Lexer, Logos};
-use malachite::Natural;
+use malachite::{Natural, base::num::conversion::traits::FromStringBase};
+
+/// Error returned when an expression cannot be evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// The input contains characters that do not form a token
+    Lex { span: Range<usize> },
+    /// The tokens do not form an expression, the span is the offending token in the input
+    Parse { span: Range<usize>, message: String },
+    /// A variable is not defined in the store
+    UndefinedVar(String),
+    /// The exponent of a power is not a natural number that fits into a `u32`
+    InvalidExponent,
+    /// The kinds of the operands of an arithmetic operation do not fit together
+    TypeMismatch {
+        /// The operation, e.g. `"add"` or `"multiply"`
+        op: &'static str,
+        left: BoxKind,
+        right: BoxKind,
+    },
+    /// The expression uses an operation that cannot be evaluated yet
+    Unsupported,
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Lex { span } => write!(f, "lexer error at {:?}", span),
+            EvalError::Parse { span, message } => {
+                write!(f, "parse error at {:?}: {}", span, message)
+            }
+            EvalError::UndefinedVar(name) => write!(f, "undefined variable '{}'", name),
+            EvalError::InvalidExponent => write!(f, "exponent is not a natural number"),
+            EvalError::TypeMismatch { op, left, right } => {
+                write!(f, "cannot {} {:?} and {:?}", op, left, right)
+            }
+            EvalError::Unsupported => write!(f, "unsupported operation"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl From<TypeMismatchError> for EvalError {
+    fn from(err: TypeMismatchError) -> Self {
+        EvalError::TypeMismatch {
+            op: err.op,
+            left: err.left,
+            right: err.right,
+        }
+    }
+}
 
 fn parse_subscript(lex: &mut Lexer<Token>) -> Option<Natural> {
     let slice = lex.slice();
@@ -32,11 +90,23 @@ fn parse_subscript(lex: &mut Lexer<Token>) -> Option<Natural> {
     Some(result)
 }
 
+fn parse_radix(lex: &mut Lexer<Token>) -> Option<Natural> {
+    let slice = lex.slice();
+    let base = match &slice[..2] {
+        "0x" => 16,
+        "0o" => 8,
+        "0b" => 2,
+        _ => return None,
+    };
+    Natural::from_string_base(base, &slice[2..])
+}
+
 #[derive(Logos, Debug, PartialEq, Clone)]
 #[logos(skip r"[ \t\n\f]+")]
 pub enum Token {
     // Match numbers
     #[regex(r"[0-9]+", |lex|lex.slice().parse())]
+    #[regex(r"0x[0-9a-fA-F]+|0o[0-7]+|0b[01]+", parse_radix)]
     Number(Natural),
     // Match Vars like 'alpha'
     #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice().to_string())]
@@ -73,7 +143,7 @@ pub enum Token {
     RedEmpty,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Num(Natural),
     Var(String),
@@ -82,6 +152,7 @@ pub enum Expr {
     Sub(Box<Expr>, Box<Expr>),
     Mul(Box<Expr>, Box<Expr>),
     Div(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
     Unixel(Box<Expr>),
     Vexel(Vec<Expr>),
     Pixel(Box<Expr>, Box<Expr>),
@@ -220,10 +291,20 @@ pub fn parser<'src>()
                 .or(box_parser(p.clone()))
                 .or(parenthesized);
 
+            // powers are right-associative and bind tighter than the unary minus, so a
+            // negative exponent has to be parenthesized
+            let power = base_atom
+                .clone()
+                .then_ignore(just(Token::Caret))
+                .repeated()
+                .foldr(base_atom, |lhs, rhs| {
+                    Expr::Pow(Box::new(lhs), Box::new(rhs))
+                });
+
             just(Token::Minus)
                 .repeated()
                 .collect::<Vec<_>>()
-                .then(base_atom)
+                .then(power)
                 .map(|(minuses, mut expr)| {
                     for _ in minuses {
                         expr = Expr::Neg(Box::new(expr));
@@ -259,31 +340,41 @@ pub fn parser<'src>()
 }
 
 impl Expr {
-    pub fn eval(&self, store: &BoxStore) -> BoxVariant {
-        match self {
+    pub fn eval(&self, store: &BoxStore) -> Result<BoxVariant, EvalError> {
+        let value = match self {
             Expr::BlackEmpty => BoxVariant::Empty(BoxValue::zero()),
             Expr::RedEmpty => BoxVariant::Empty(BoxValue::anti_zero()),
             Expr::Subscript(n, v) => {
-                let mut variant = v.eval(store);
+                let mut variant = v.eval(store)?;
                 variant.set_multiplicity(0, n.clone());
                 variant
             }
             Expr::Num(n) => BoxVariant::Num(BoxValue::from(n.clone())),
-            Expr::Neg(rhs) => BoxVariant::Num(BoxValue::from(-1)) * rhs.eval(store),
-            Expr::Add(lhs, rhs) => lhs.eval(store) + rhs.eval(store),
-            Expr::Mul(lhs, rhs) => lhs.eval(store) * rhs.eval(store),
+            Expr::Neg(rhs) => BoxVariant::Num(BoxValue::from(-1)).try_mul(rhs.eval(store)?)?,
+            Expr::Add(lhs, rhs) => lhs.eval(store)?.try_add(rhs.eval(store)?)?,
+            Expr::Mul(lhs, rhs) => lhs.eval(store)?.try_mul(rhs.eval(store)?)?,
             Expr::Sub(lhs, rhs) => {
-                lhs.eval(store) + BoxVariant::Num(BoxValue::from(-1)) * rhs.eval(store)
+                let negated = BoxVariant::Num(BoxValue::from(-1)).try_mul(rhs.eval(store)?)?;
+                lhs.eval(store)?.try_add(negated)?
+            }
+            Expr::Pow(lhs, rhs) => {
+                let base = lhs.eval(store)?;
+                let exp = rhs
+                    .eval(store)?
+                    .into_any_raw()
+                    .try_to_u32()
+                    .ok_or(EvalError::InvalidExponent)?;
+                base.try_pow(exp)?
             }
             // Expr::Div(lhs, rhs) => todo!(),
             Expr::Var(name) => store
                 .fetch_box_by_name(name)
-                .expect("Undefined Var assignment"),
-            Expr::Unixel(x) => BoxVariant::Unixel(BoxValue::unixel(x.eval(store).into_any_raw())),
+                .ok_or_else(|| EvalError::UndefinedVar(name.clone()))?,
+            Expr::Unixel(x) => BoxVariant::Unixel(BoxValue::unixel(x.eval(store)?.into_any_raw())),
             Expr::Vexel(xs) => {
                 let mut vs = Vec::new();
                 for x in xs {
-                    let variant = x.eval(store);
+                    let variant = x.eval(store)?;
                     match variant {
                         BoxVariant::Unixel(v) => vs.push(v),
                         _ => unreachable!(),
@@ -292,13 +383,13 @@ impl Expr {
                 BoxVariant::Vexel(vs.into())
             }
             Expr::Pixel(x, y) => BoxVariant::Pixel(BoxValue::pixel(
-                x.eval(store).into_any_raw(),
-                y.eval(store).into_any_raw(),
+                x.eval(store)?.into_any_raw(),
+                y.eval(store)?.into_any_raw(),
             )),
             Expr::Maxel(pxs) => {
                 let mut vs = Vec::new();
                 for px in pxs {
-                    let variant = px.eval(store);
+                    let variant = px.eval(store)?;
                     match variant {
                         BoxVariant::Pixel(px) => vs.push(px),
                         _ => unreachable!(),
@@ -310,23 +401,212 @@ impl Expr {
             Expr::Box(bxs) => {
                 let mut vs = Vec::new();
                 for bx in bxs {
-                    let var = bx.eval(store).into_any();
+                    let var = bx.eval(store)?.into_any();
                     vs.push(var.into_any_raw());
                 }
                 BoxVariant::Any(vs.into())
             }
-            _ => todo!(),
+            _ => return Err(EvalError::Unsupported),
+        };
+        Ok(value)
+    }
+}
+
+/// Tokenize and parse a single expression
+///
+/// Parse errors report the span of the first unexpected token in the input, or the end of the
+/// input if it ends too early, e.g. because of an unclosed parenthesis.
+pub fn parse_expr(input: &str) -> Result<Expr, EvalError> {
+    let mut tokens = Vec::new();
+    let mut spans = Vec::new();
+    for (token, span) in Token::lexer(input).spanned() {
+        match token {
+            Ok(token) => tokens.push(token),
+            Err(_) => return Err(EvalError::Lex { span }),
+        }
+        spans.push(span);
+    }
+
+    parser().parse(&tokens).into_result().map_err(|errs| {
+        let err = &errs[0];
+        let span = spans
+            .get(err.span().start)
+            .cloned()
+            .unwrap_or(input.len()..input.len());
+        let message = match err.found() {
+            Some(token) => format!("unexpected {:?}", token),
+            None => "unexpected end of input".to_string(),
+        };
+        EvalError::Parse { span, message }
+    })
+}
+
+/// Tokenize, parse and evaluate a single expression with the variables of the store
+pub fn evaluate_expr_in(input: &str, store: &BoxStore) -> Result<BoxVariant, EvalError> {
+    parse_expr(input)?.eval(store)
+}
+
+/// Tokenize, parse and evaluate a single expression
+///
+/// Expressions consist of natural numbers, the variable `alpha`, the binary operators `+`, `-`,
+/// `*` and `^`, the unary minus and parentheses. Powers bind tighter than products, which bind
+/// tighter than sums.
+pub fn evaluate_expr(input: &str) -> Result<BoxVariant, EvalError> {
+    let mut store = BoxStore::new();
+    store.store_box_with_name("alpha", BoxValue::alpha());
+    evaluate_expr_in(input, &store)
+}
+
+/// Cursor over the input of [`parse_polynomial_in`]
+struct PolyCursor<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl PolyCursor<'_> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    /// Consume the character if it comes next, skipping whitespace before it
+    fn eat(&mut self, ch: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(ch) {
+            self.pos += ch.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume a decimal natural number if one comes next
+    fn natural(&mut self) -> Option<Natural> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if len == 0 {
+            return None;
+        }
+        self.pos += len;
+        Natural::from_string_base(10, &rest[..len])
+    }
+
+    /// Consume the variable if it comes next as a whole word
+    fn variable(&mut self, var: &str) -> bool {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let whole_word = rest
+            .strip_prefix(var)
+            .is_some_and(|after| !after.starts_with(|c: char| c.is_alphanumeric() || c == '_'));
+        if whole_word {
+            self.pos += var.len();
+        }
+        whole_word
+    }
+
+    fn error(&self, message: &str) -> EvalError {
+        let len = self.peek().map_or(0, char::len_utf8);
+        EvalError::Parse {
+            span: self.pos..self.pos + len,
+            message: message.to_string(),
         }
     }
 }
 
+/// Parse a polynomial in `alpha` or `x` written in ordinary notation, e.g. `3x^2 + 2x - 1`
+///
+/// The variable is `x` if the input contains it as a word of letters and `alpha` otherwise. See
+/// [`parse_polynomial_in`] for the syntax.
+pub fn parse_polynomial(input: &str) -> Result<BoxValue<PolynumBox>, EvalError> {
+    let uses_x = input
+        .split(|c: char| !c.is_alphabetic())
+        .any(|word| word == "x");
+    parse_polynomial_in(input, if uses_x { "x" } else { "alpha" })
+}
+
+/// Parse a polynomial in the given variable written in ordinary notation
+///
+/// A term is a natural coefficient, the variable with an optional natural exponent after `^`,
+/// or a coefficient followed by the variable with an optional `*` in between. A missing
+/// coefficient is one and a missing exponent is one. Terms are separated by `+` or `-`, the
+/// first term may be preceded by a sign, and a minus turns the term into an anti-box term.
+/// Parse errors report the span of the character that does not fit.
+pub fn parse_polynomial_in(input: &str, var: &str) -> Result<BoxValue<PolynumBox>, EvalError> {
+    let mut cursor = PolyCursor { input, pos: 0 };
+    let mut terms: Vec<(BoxValue<AnyBox>, Natural)> = Vec::new();
+
+    let mut negative = cursor.eat('-');
+    if !negative {
+        cursor.eat('+');
+    }
+    loop {
+        let coeff = cursor.natural();
+        let has_var = if coeff.is_some() {
+            let star = cursor.eat('*');
+            let has_var = cursor.variable(var);
+            if star && !has_var {
+                return Err(cursor.error("expected the variable after '*'"));
+            }
+            has_var
+        } else if cursor.variable(var) {
+            true
+        } else {
+            return Err(cursor.error("expected a coefficient or the variable"));
+        };
+
+        let exponent = if has_var {
+            if cursor.eat('^') {
+                cursor
+                    .natural()
+                    .ok_or_else(|| cursor.error("expected a natural exponent"))?
+            } else {
+                Natural::from(1_u32)
+            }
+        } else {
+            Natural::from(0_u32)
+        };
+
+        let mut term = if exponent == 0 {
+            BoxValue::zero().cast::<AnyBox>()
+        } else {
+            BoxValue::from(exponent).cast::<AnyBox>()
+        };
+        if negative {
+            term = term.into_anti();
+        }
+        terms.push((term, coeff.unwrap_or(Natural::from(1_u32))));
+
+        if cursor.eat('+') {
+            negative = false;
+        } else if cursor.eat('-') {
+            negative = true;
+        } else {
+            cursor.skip_whitespace();
+            if cursor.pos < input.len() {
+                return Err(cursor.error("expected '+' or '-'"));
+            }
+            break;
+        }
+    }
+
+    Ok(BoxValue::from_terms(terms))
+}
+
 #[cfg(test)]
 mod tests {
     use logos::Logos;
 
     use crate::{
-        BoxValue,
-        parser::{Parser, Token, parser},
+        BoxKind, BoxValue, BoxVariant,
+        parser::{
+            EvalError, Expr, Parser, Token, evaluate_expr, parse_expr, parse_polynomial,
+            parse_polynomial_in, parser,
+        },
         store::BoxStore,
     };
 
@@ -362,7 +642,7 @@ mod tests {
         };
 
         // evaluates the AST to get the result
-        let val = ast.eval(&store);
+        let val = ast.eval(&store).unwrap();
         println!("\n[result]\n{:#}", val);
 
         // let input = "⌊⌈1,1⌉,⌈1,2⌉,₂⌈2,2⌉⌋";
@@ -393,8 +673,159 @@ mod tests {
         };
 
         // evaluates the AST to get the result
-        let val = ast.eval(&store);
+        let val = ast.eval(&store).unwrap();
 
         println!("\n[result]\n{:#}", val);
     }
+
+    #[test]
+    fn test_evaluate_expr() {
+        let alpha = BoxVariant::alpha();
+        let exp = BoxVariant::from(2) + BoxVariant::from(3) * alpha.clone() * alpha.clone();
+        assert_eq!(evaluate_expr("2 + 3 * alpha^2"), Ok(exp));
+
+        let exp = BoxVariant::from(5) * alpha.clone();
+        assert_eq!(evaluate_expr("(2 + 3) * alpha"), Ok(exp));
+        assert_eq!(evaluate_expr("2 ^ 3 ^ 2"), Ok(BoxVariant::from(512)));
+        assert_eq!(evaluate_expr("-2^2"), Ok(BoxVariant::from(-4)));
+        assert_eq!(evaluate_expr("((1 + alpha)) ^ 0"), Ok(BoxVariant::one()));
+        let exp = BoxValue::from(1) + BoxValue::alpha();
+        assert_eq!(
+            evaluate_expr("(1 + alpha) ^ 13"),
+            Ok(BoxVariant::repack_raw(exp.pow(13)))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_expr_errors() {
+        assert_eq!(evaluate_expr("2 $ 3"), Err(EvalError::Lex { span: 2..3 }));
+        assert_eq!(
+            evaluate_expr("beta + 1"),
+            Err(EvalError::UndefinedVar("beta".to_string()))
+        );
+        assert_eq!(evaluate_expr("2 ^ alpha"), Err(EvalError::InvalidExponent));
+        assert_eq!(evaluate_expr("2 / 3"), Err(EvalError::Unsupported));
+
+        // kinds that do not fit together are reported instead of panicking
+        let err = evaluate_expr("⌊□⌋ * alpha").unwrap_err();
+        assert_eq!(
+            err,
+            EvalError::TypeMismatch {
+                op: "multiply",
+                left: BoxKind::Any,
+                right: BoxKind::Polynum,
+            }
+        );
+        assert_eq!(err.to_string(), "cannot multiply Any and Polynum");
+        assert!(matches!(
+            evaluate_expr("⌊□⌋ + 1"),
+            Err(EvalError::TypeMismatch { op: "add", .. })
+        ));
+        assert!(matches!(
+            evaluate_expr("⌊□⌋ ^ 2"),
+            Err(EvalError::TypeMismatch { .. })
+        ));
+    }
+
+    fn num(n: u32) -> Box<Expr> {
+        Box::new(Expr::Num(n.into()))
+    }
+
+    fn var(name: &str) -> Box<Expr> {
+        Box::new(Expr::Var(name.to_string()))
+    }
+
+    #[test]
+    fn test_precedence() {
+        let exp = Expr::Add(
+            num(2),
+            Box::new(Expr::Mul(num(3), Box::new(Expr::Pow(var("alpha"), num(2))))),
+        );
+        assert_eq!(parse_expr("2 + 3 * alpha ^ 2"), Ok(exp));
+
+        let exp = Expr::Mul(Box::new(Expr::Add(num(2), num(3))), var("alpha"));
+        assert_eq!(parse_expr("(2 + 3) * alpha"), Ok(exp));
+
+        let exp = Expr::Pow(num(2), Box::new(Expr::Pow(num(3), num(2))));
+        assert_eq!(parse_expr("2 ^ 3 ^ 2"), Ok(exp));
+
+        let exp = Expr::Sub(
+            Box::new(Expr::Neg(Box::new(Expr::Pow(var("alpha"), num(2))))),
+            num(1),
+        );
+        assert_eq!(parse_expr("-alpha^2 - 1"), Ok(exp));
+
+        let exp = Expr::Mul(num(2), Box::new(Expr::Add(num(1), var("alpha"))));
+        assert_eq!(parse_expr("2 * ((1 + (alpha)))"), Ok(exp));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_expr("(2 + 3").unwrap_err();
+        assert!(matches!(err, EvalError::Parse { span, .. } if span == (6..6)));
+
+        let err = parse_expr("2 + 3) * alpha").unwrap_err();
+        assert!(matches!(err, EvalError::Parse { span, .. } if span == (5..6)));
+
+        let err = parse_expr("2 * (alpha + )").unwrap_err();
+        assert!(matches!(err, EvalError::Parse { span, .. } if span == (13..14)));
+    }
+
+    #[test]
+    fn test_parse_polynomial() {
+        let alpha = BoxValue::alpha();
+        assert_eq!(
+            parse_polynomial("3x^2 - 1"),
+            Ok(BoxValue::from(3) * alpha.clone().pow(2) + BoxValue::from(1).into_anti())
+        );
+        assert_eq!(
+            parse_polynomial("x^3 + x - 4"),
+            Ok(alpha.clone().pow(3) + alpha.clone() + BoxValue::from(-4))
+        );
+        assert_eq!(
+            parse_polynomial("-alpha^2 + 2*alpha"),
+            Ok(BoxValue::from(-1) * alpha.clone().pow(2) + BoxValue::from(2) * &alpha)
+        );
+        assert_eq!(parse_polynomial("7"), Ok(BoxValue::from(7).cast()));
+        assert_eq!(
+            parse_polynomial_in("2t + t - 3t", "t"),
+            Ok(BoxValue::zero().cast())
+        );
+
+        assert!(matches!(
+            parse_polynomial("3x^ + 1"),
+            Err(EvalError::Parse { span, .. }) if span == (4..5)
+        ));
+        assert!(matches!(
+            parse_polynomial("3x 2"),
+            Err(EvalError::Parse { span, .. }) if span == (3..4)
+        ));
+        assert!(matches!(
+            parse_polynomial("2 + y"),
+            Err(EvalError::Parse { span, .. }) if span == (4..5)
+        ));
+        assert!(parse_polynomial("").is_err());
+    }
+
+    #[test]
+    fn test_radix_literals() {
+        let exp = evaluate_expr("255 * alpha");
+        assert_eq!(evaluate_expr("0xFF * alpha"), exp);
+        assert_eq!(evaluate_expr("0o377 * alpha"), exp);
+        assert_eq!(evaluate_expr("0b11111111 * alpha"), exp);
+        assert_eq!(evaluate_expr("0xff"), Ok(BoxVariant::from(255)));
+        assert_eq!(evaluate_expr("042"), Ok(BoxVariant::from(42)));
+
+        let big = evaluate_expr("0x10000000000000000").unwrap();
+        assert_eq!(big, BoxVariant::from(u64::MAX) + BoxVariant::from(1));
+
+        assert!(matches!(
+            evaluate_expr("0b102"),
+            Err(EvalError::Parse { span, .. }) if span == (4..5)
+        ));
+        assert_eq!(
+            evaluate_expr("2 ^ 0x100000000"),
+            Err(EvalError::InvalidExponent)
+        );
+    }
 }
@@ -0,0 +1,123 @@
+//! Dense polynomials with generic coefficients as a fast numeric view of polynumbers
+
+use std::ops::{Add, Mul};
+
+use malachite::{Integer, base::num::basic::traits::Zero};
+
+use crate::{BoxValue, PolynumBox};
+
+/// Dense polynomial in alpha with coefficients indexed by degree
+///
+/// The coefficients can be any ring-like type, e.g. `i64`, [`Integer`] or elements of a finite
+/// field. Polynumbers remain the canonical representation, this type is only a numeric view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Poly<C> {
+    coeffs: Vec<C>,
+}
+
+impl<C: Zero + PartialEq> Poly<C> {
+    /// Construct a polynomial from its coefficients indexed by degree
+    pub fn new(mut coeffs: Vec<C>) -> Self {
+        while coeffs.last().is_some_and(|c| *c == C::ZERO) {
+            coeffs.pop();
+        }
+        Self { coeffs }
+    }
+
+    /// Return the coefficients indexed by degree without trailing zeros
+    pub fn coefficients(&self) -> &[C] {
+        &self.coeffs
+    }
+
+    /// Return the degree of the polynomial, which is `None` for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+}
+
+impl<C: Clone + Into<Integer>> Poly<C> {
+    /// Convert the polynomial into a polynumber
+    pub fn to_box(&self) -> BoxValue<PolynumBox> {
+        let coeffs: Vec<Integer> = self.coeffs.iter().cloned().map(Into::into).collect();
+        BoxValue::from_dense(&coeffs)
+    }
+}
+
+impl<C: Zero + PartialEq + Clone + Add<Output = C>> Add for Poly<C> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let (mut long, short) = if self.coeffs.len() >= rhs.coeffs.len() {
+            (self.coeffs, rhs.coeffs)
+        } else {
+            (rhs.coeffs, self.coeffs)
+        };
+
+        for (l, s) in long.iter_mut().zip(short) {
+            *l = l.clone() + s;
+        }
+        Poly::new(long)
+    }
+}
+
+impl<C: Zero + PartialEq + Clone + Add<Output = C> + Mul<Output = C>> Mul for Poly<C> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        if self.coeffs.is_empty() || rhs.coeffs.is_empty() {
+            return Poly::new(Vec::new());
+        }
+
+        let mut coeffs = vec![C::ZERO; self.coeffs.len() + rhs.coeffs.len() - 1];
+        for (i, l) in self.coeffs.iter().enumerate() {
+            for (j, r) in rhs.coeffs.iter().enumerate() {
+                coeffs[i + j] = coeffs[i + j].clone() + l.clone() * r.clone();
+            }
+        }
+        Poly::new(coeffs)
+    }
+}
+
+impl BoxValue<PolynumBox> {
+    /// Convert the polynumber into a dense polynomial
+    ///
+    /// Returns `None` if the box is not a plain polynumber or if a coefficient does not fit
+    /// into `C`.
+    pub fn to_poly<C>(&self) -> Option<Poly<C>>
+    where
+        C: Zero + PartialEq + for<'a> TryFrom<&'a Integer>,
+    {
+        let coeffs = self
+            .to_dense()?
+            .iter()
+            .map(|c| C::try_from(c).ok())
+            .collect::<Option<Vec<C>>>()?;
+        Some(Poly::new(coeffs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{BoxValue, poly::Poly};
+
+    #[test]
+    fn test_poly() {
+        let left = Poly::new(vec![1_i64, -1]);
+        let right = Poly::new(vec![1_i64, 1, 0]);
+        assert_eq!(right.degree(), Some(1));
+
+        let prod = left.clone() * right.clone();
+        assert_eq!(prod, Poly::new(vec![1, 0, -1]));
+        assert_eq!(left + right, Poly::new(vec![2]));
+
+        let alpha = BoxValue::alpha();
+        let exp = BoxValue::from(1) + (-1) * alpha.clone() * alpha.clone();
+        assert_eq!(prod.to_box(), exp);
+        assert_eq!(exp.to_poly::<i64>(), Some(prod));
+
+        let big = BoxValue::from(u64::MAX) * alpha;
+        assert_eq!(big.to_poly::<i64>(), None);
+        assert_eq!(big.to_poly::<u64>(), Some(Poly::new(vec![0, u64::MAX])));
+    }
+}
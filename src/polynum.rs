@@ -0,0 +1,1131 @@
+//! Polynumbers read as polynomials in alpha
+
+use std::cmp::Ordering;
+
+use malachite::{
+    Integer, Natural, Rational,
+    base::{
+        num::{
+            arithmetic::traits::{CheckedSub, Gcd, Parity, Pow, UnsignedAbs},
+            conversion::traits::RoundingFrom,
+        },
+        rounding_modes::RoundingMode,
+    },
+};
+
+use crate::{AnyBox, BoxKind, BoxValue, Color, PolynumBox};
+
+/// Remove the zero coefficients of the highest degrees
+fn trim(coeffs: &mut Vec<Integer>) {
+    while coeffs.last().is_some_and(|c| *c == 0) {
+        coeffs.pop();
+    }
+}
+
+/// Raise `x` to the power `exp` with checked `i64` arithmetic
+///
+/// The bases zero and one and minus one never overflow, so their exponent may be arbitrarily
+/// large.
+fn checked_power(x: i64, exp: &Natural) -> Option<i64> {
+    match x {
+        0 if *exp == 0 => Some(1),
+        0 => Some(0),
+        1 => Some(1),
+        -1 if exp.even() => Some(1),
+        -1 => Some(-1),
+        _ => x.checked_pow(u32::try_from(exp).ok()?),
+    }
+}
+
+/// Raise `x` to the power `exp`
+///
+/// Like [`checked_power`] the bases zero and one and minus one take any exponent. Returns
+/// `None` for other bases if the exponent does not fit into a `u64`.
+fn integer_power(x: &Integer, exp: &Natural) -> Option<Integer> {
+    if *x == 0 || *x == 1 || *x == -1 {
+        let value = i64::try_from(x).ok()?;
+        return Some(Integer::from(checked_power(value, exp)?));
+    }
+    Some(x.pow(u64::try_from(exp).ok()?))
+}
+
+/// Long division of two dense polynomials with exact integer coefficients
+///
+/// Returns `None` if the divisor is zero or if a leading coefficient does not divide evenly.
+pub(crate) fn dense_div_rem(
+    num: &[Integer],
+    den: &[Integer],
+) -> Option<(Vec<Integer>, Vec<Integer>)> {
+    let mut den = den.to_vec();
+    trim(&mut den);
+    let lead = den.last()?.clone();
+
+    let mut rem = num.to_vec();
+    trim(&mut rem);
+    if rem.len() < den.len() {
+        return Some((Vec::new(), rem));
+    }
+
+    let mut quot = vec![Integer::from(0); rem.len() - den.len() + 1];
+    while rem.len() >= den.len() {
+        let top = rem.last()?.clone();
+        if &top % &lead != 0 {
+            return None;
+        }
+
+        let factor = top / &lead;
+        let shift = rem.len() - den.len();
+        for (i, c) in den.iter().enumerate() {
+            rem[shift + i] -= &factor * c;
+        }
+        quot[shift] = factor;
+        trim(&mut rem);
+    }
+
+    trim(&mut quot);
+    Some((quot, rem))
+}
+
+/// Greatest common divisor of the coefficients, which is zero for the zero polynomial
+fn content(coeffs: &[Integer]) -> Natural {
+    coeffs
+        .iter()
+        .fold(Natural::from(0_u32), |acc, c| acc.gcd(c.unsigned_abs()))
+}
+
+/// Divide a polynomial by its content
+fn primitive_part(coeffs: &[Integer]) -> Vec<Integer> {
+    let content = Integer::from(content(coeffs));
+    if content == 0 {
+        return Vec::new();
+    }
+    coeffs.iter().map(|c| c / &content).collect()
+}
+
+/// Greatest common divisor of two dense polynomials over the integers
+///
+/// The primitive part is computed with pseudo-remainders so that all coefficients stay
+/// integral. The result has a positive leading coefficient.
+pub(crate) fn dense_gcd(left: &[Integer], right: &[Integer]) -> Vec<Integer> {
+    let content = Integer::from(content(left).gcd(content(right)));
+
+    let mut a = primitive_part(left);
+    let mut b = primitive_part(right);
+    trim(&mut a);
+    trim(&mut b);
+    if a.len() < b.len() {
+        std::mem::swap(&mut a, &mut b);
+    }
+
+    while !b.is_empty() {
+        // pseudo-remainder of a by b
+        let lead = b.last().unwrap().clone();
+        let mut rem = a;
+        while rem.len() >= b.len() {
+            let top = rem.last().unwrap().clone();
+            let shift = rem.len() - b.len();
+            for c in rem.iter_mut() {
+                *c *= &lead;
+            }
+            for (i, c) in b.iter().enumerate() {
+                rem[shift + i] -= &top * c;
+            }
+            trim(&mut rem);
+        }
+
+        a = b;
+        b = primitive_part(&rem);
+    }
+
+    let mut gcd: Vec<Integer> = a.into_iter().map(|c| c * &content).collect();
+    if gcd.last().is_some_and(|c| *c < 0) {
+        gcd = gcd.into_iter().map(|c| -c).collect();
+    }
+    gcd
+}
+
+/// Degree and coefficient ranges of a polynumber
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolyBounds {
+    pub min_degree: Natural,
+    pub max_degree: Natural,
+    pub min_coeff: Integer,
+    pub max_coeff: Integer,
+    pub term_count: usize,
+}
+
+impl BoxValue<PolynumBox> {
+    /// Return the signed coefficients of the polynumber sorted by degree
+    ///
+    /// A plain polynumber is a black box whose children are either empty boxes (the constant
+    /// term) or number boxes `⌊□ₖ⌋` (the term alpha^k). The color of a child is the sign of its
+    /// coefficient. Returns `None` for any other structure.
+    pub(crate) fn degree_terms(&self) -> Option<Vec<(Natural, Integer)>> {
+        let kind = self.get_kind(0);
+        if !matches!(kind, BoxKind::Empty | BoxKind::Num | BoxKind::Polynum) || self.is_anti() {
+            return None;
+        }
+
+        let mut terms: Vec<(Natural, Integer)> = Vec::new();
+        for child in self {
+            let degree = match (child.kinds[0], child.lengths[0]) {
+                (BoxKind::Empty | BoxKind::Num, 1) => Natural::from(0_u32),
+                (BoxKind::Num, 2)
+                    if child.kinds[1] == BoxKind::Empty && child.colors[1] == Color::Black =>
+                {
+                    child.multiplicities[1].clone()
+                }
+                _ => return None,
+            };
+
+            let coeff = Integer::from(child.multiplicities[0].clone());
+            let coeff = match child.colors[0] {
+                Color::Black => coeff,
+                Color::Red => -coeff,
+            };
+            terms.push((degree, coeff));
+        }
+
+        // merge terms of equal degree in case the box was not built by box arithmetic
+        terms.sort_by(|(left, _), (right, _)| left.cmp(right));
+        let mut merged: Vec<(Natural, Integer)> = Vec::with_capacity(terms.len());
+        for (degree, coeff) in terms {
+            match merged.last_mut() {
+                Some((last_degree, last_coeff)) if *last_degree == degree => *last_coeff += coeff,
+                _ => merged.push((degree, coeff)),
+            }
+        }
+        merged.retain(|(_, coeff)| *coeff != 0);
+
+        Some(merged)
+    }
+
+    /// Return the dense coefficients of the polynumber indexed by degree
+    pub(crate) fn to_dense(&self) -> Option<Vec<Integer>> {
+        let terms = self.degree_terms()?;
+        let len = match terms.last() {
+            Some((degree, _)) => usize::try_from(degree).ok()? + 1,
+            None => 0,
+        };
+
+        let mut coeffs = vec![Integer::from(0); len];
+        for (degree, coeff) in terms {
+            coeffs[usize::try_from(&degree).ok()?] = coeff;
+        }
+        Some(coeffs)
+    }
+
+    /// Construct the polynumber with the given dense coefficients indexed by degree
+    pub(crate) fn from_dense(coeffs: &[Integer]) -> Self {
+        Self::from_degree_terms(
+            coeffs
+                .iter()
+                .enumerate()
+                .map(|(degree, coeff)| (Natural::from(degree as u64), coeff.clone())),
+        )
+    }
+
+    /// Construct the polynumber with the given degrees and signed coefficients
+    ///
+    /// This is the inverse of [`BoxValue::degree_terms`], the degrees must be distinct and terms
+    /// with coefficient zero are skipped.
+    pub(crate) fn from_degree_terms(terms: impl IntoIterator<Item = (Natural, Integer)>) -> Self {
+        let mut result = BoxValue::<PolynumBox>::new();
+        result.kinds.push(BoxKind::Polynum);
+        result.colors.push(Color::Black);
+        result.multiplicities.push(Natural::from(1_u32));
+        result.lengths.push(1);
+
+        let mut max_depth = 0;
+        for (degree, coeff) in terms {
+            if coeff == 0 {
+                continue;
+            }
+
+            let mut term = if degree == 0 {
+                BoxValue::zero().cast::<AnyBox>()
+            } else {
+                BoxValue::from(degree).cast::<AnyBox>()
+            };
+            if coeff < 0 {
+                term.set_color(0, Color::Red);
+            }
+
+            max_depth = max_depth.max(term.get_length(0));
+            result.extend_with_mul(term, coeff.unsigned_abs());
+        }
+
+        result.kinds[0] = BoxKind::get_kind_from_depth(max_depth);
+        result.sort_immediate_children();
+        result
+    }
+
+    /// Return the coefficients of the polynumber indexed by degree without trailing zeros
+    ///
+    /// Returns `None` if the box is not a plain polynumber.
+    pub fn to_coefficients(&self) -> Option<Vec<Integer>> {
+        self.to_dense()
+    }
+
+    /// Return the degrees and signed coefficients of the non-zero terms sorted by degree
+    ///
+    /// Terms of equal degree are merged. Returns `None` if the box is not a plain polynumber.
+    pub fn terms_by_degree(&self) -> Option<Vec<(Natural, Integer)>> {
+        self.degree_terms()
+    }
+
+    /// Return the common degree of all terms if the polynumber is homogeneous
+    ///
+    /// A non-zero constant has degree zero. Returns `None` for the zero box, for terms of
+    /// mixed degrees and if the box is not a plain polynumber.
+    pub fn is_homogeneous(&self) -> Option<Natural> {
+        let terms = self.degree_terms()?;
+        let (degree, _) = terms.first()?;
+        terms
+            .iter()
+            .all(|(other, _)| other == degree)
+            .then(|| degree.clone())
+    }
+
+    /// Test if the coefficients read the same from both ends
+    ///
+    /// For a polynumber of degree `d` the coefficients of alpha^k and alpha^(d - k) must be
+    /// equal including their signs. Returns `None` for the zero box and if the box is not a
+    /// plain polynumber.
+    pub fn is_palindromic(&self) -> Option<bool> {
+        let terms = self.degree_terms()?;
+        let (degree, _) = terms.last()?;
+        // the k-th lowest term has to mirror the k-th highest term
+        Some(
+            terms
+                .iter()
+                .zip(terms.iter().rev())
+                .all(|((low, low_coeff), (high, high_coeff))| {
+                    low + high == *degree && low_coeff == high_coeff
+                }),
+        )
+    }
+
+    /// Construct the ordinary generating function of a finite sequence
+    ///
+    /// The entries of the sequence are the coefficients indexed by degree, negative entries
+    /// become anti-box terms.
+    pub fn from_sequence(seq: &[i64]) -> Self {
+        let coeffs: Vec<Integer> = seq.iter().map(|&c| Integer::from(c)).collect();
+        Self::from_dense(&coeffs)
+    }
+
+    /// Construct the polynumber whose coefficient of alpha^k is `coeffs[k]`
+    ///
+    /// Negative coefficients become anti-box terms and zeros are skipped, so this is the
+    /// inverse of [`BoxValue::to_coefficients`]. An empty slice gives the zero box.
+    pub fn from_coefficients(coeffs: &[i64]) -> Self {
+        Self::from_sequence(coeffs)
+    }
+
+    /// Return the generating function reading of the polynumber
+    ///
+    /// Each pair `(n, c)` states that there are `c` objects of size `n`, sorted by size.
+    /// Returns `None` if the box is not a plain polynumber with non-negative coefficients.
+    pub fn gf_terms(&self) -> Option<Vec<(Natural, Natural)>> {
+        self.degree_terms()?
+            .into_iter()
+            .map(|(degree, coeff)| Natural::try_from(coeff).ok().map(|c| (degree, c)))
+            .collect()
+    }
+
+    /// Return the k-th moment of the size distribution given by the generating function
+    ///
+    /// The coefficients are normalized to a probability distribution over the degrees, so
+    /// `moment(1)` is the mean size. Returns `None` if [`BoxValue::gf_terms`] is `None` or if the
+    /// polynumber is zero.
+    pub fn moment(&self, k: u64) -> Option<f64> {
+        let terms = self.gf_terms()?;
+        if terms.is_empty() {
+            return None;
+        }
+
+        let mut weighted = Natural::from(0_u32);
+        let mut total = Natural::from(0_u32);
+        for (degree, coeff) in terms {
+            weighted += degree.pow(k) * &coeff;
+            total += coeff;
+        }
+
+        let moment = Rational::from_naturals(weighted, total);
+        Some(f64::rounding_from(&moment, RoundingMode::Nearest).0)
+    }
+
+    /// Return the Shannon entropy in bits of the normalized coefficient distribution
+    ///
+    /// The entropy of the zero polynumber is zero. Returns `None` if [`BoxValue::gf_terms`] is
+    /// `None`.
+    pub fn coefficient_entropy(&self) -> Option<f64> {
+        let terms = self.gf_terms()?;
+        let total: Natural = terms.iter().map(|(_, coeff)| coeff).sum();
+
+        let mut entropy = 0.0;
+        for (_, coeff) in terms {
+            let p = Rational::from_naturals(coeff, total.clone());
+            let p = f64::rounding_from(&p, RoundingMode::Nearest).0;
+            entropy -= p * p.log2();
+        }
+        Some(entropy)
+    }
+
+    /// Return the squarefree part of the polynumber
+    ///
+    /// This divides the polynumber by the greatest common divisor with its derivative, which
+    /// removes all repeated factors. The result is determined up to a constant factor. Returns
+    /// `None` if the box is not a plain polynumber.
+    pub fn squarefree_part(&self) -> Option<Self> {
+        let coeffs = self.to_dense()?;
+        if coeffs.is_empty() {
+            return Some(self.clone());
+        }
+
+        let der = self.clone().derivative().to_dense()?;
+        let gcd = dense_gcd(&coeffs, &der);
+        let (quot, _) = dense_div_rem(&coeffs, &gcd)?;
+        Some(Self::from_dense(&quot))
+    }
+
+    /// Return how many times the factor `alpha - root` divides the polynumber
+    ///
+    /// Returns `None` if the box is not a plain polynumber or if it is zero.
+    pub fn root_multiplicity(&self, root: impl Into<Integer>) -> Option<usize> {
+        let mut coeffs = self.to_dense()?;
+        if coeffs.is_empty() {
+            return None;
+        }
+
+        let factor = [-root.into(), Integer::from(1)];
+        let mut count = 0;
+        while let Some((quot, rem)) = dense_div_rem(&coeffs, &factor)
+            && rem.is_empty()
+        {
+            coeffs = quot;
+            count += 1;
+        }
+        Some(count)
+    }
+
+    /// Return the degree and coefficient ranges of the non-zero terms in a single pass
+    ///
+    /// Returns `None` if the box is not a plain polynumber or if it is zero.
+    pub fn bounds(&self) -> Option<PolyBounds> {
+        let mut terms = self.degree_terms()?.into_iter();
+        let (degree, coeff) = terms.next()?;
+        let mut bounds = PolyBounds {
+            min_degree: degree.clone(),
+            max_degree: degree,
+            min_coeff: coeff.clone(),
+            max_coeff: coeff,
+            term_count: 1,
+        };
+
+        for (degree, coeff) in terms {
+            if degree < bounds.min_degree {
+                bounds.min_degree = degree.clone();
+            }
+            if degree > bounds.max_degree {
+                bounds.max_degree = degree;
+            }
+            if coeff < bounds.min_coeff {
+                bounds.min_coeff = coeff.clone();
+            }
+            if coeff > bounds.max_coeff {
+                bounds.max_coeff = coeff;
+            }
+            bounds.term_count += 1;
+        }
+        Some(bounds)
+    }
+
+    /// Return the signed coefficient of the highest power of alpha
+    ///
+    /// The leading coefficient of the zero box is zero. Returns `None` if the box is not a
+    /// plain polynumber.
+    pub fn leading_coefficient(&self) -> Option<Integer> {
+        let terms = self.degree_terms()?;
+        Some(
+            terms
+                .last()
+                .map_or(Integer::from(0), |(_, coeff)| coeff.clone()),
+        )
+    }
+
+    /// Return the signed coefficient of alpha^0
+    ///
+    /// The constant term of the zero box is zero. Returns `None` if the box is not a plain
+    /// polynumber.
+    pub fn constant_term(&self) -> Option<Integer> {
+        let terms = self.degree_terms()?;
+        match terms.first() {
+            Some((degree, coeff)) if *degree == 0 => Some(coeff.clone()),
+            _ => Some(Integer::from(0)),
+        }
+    }
+
+    /// Return the polynumber whose coefficients are the forward differences of the coefficients
+    ///
+    /// The coefficient of degree `n` in the result is the difference of the coefficients of
+    /// degree `n + 1` and `n`. Returns `None` if the box is not a plain polynumber.
+    pub fn finite_difference(&self) -> Option<Self> {
+        let coeffs = self.to_dense()?;
+        let diffs: Vec<Integer> = coeffs.windows(2).map(|w| &w[1] - &w[0]).collect();
+        Some(Self::from_dense(&diffs))
+    }
+
+    /// Drop every term whose degree exceeds `max_degree`
+    ///
+    /// The remaining terms keep their coefficients. Returns `None` if the box is not a plain
+    /// polynumber.
+    pub fn truncate_degree(&self, max_degree: impl Into<Natural>) -> Option<Self> {
+        let max_degree = max_degree.into();
+        let mut terms = self.degree_terms()?;
+        terms.retain(|(degree, _)| *degree <= max_degree);
+        Some(Self::from_degree_terms(terms))
+    }
+
+    /// Add `k` to the exponent of every term
+    ///
+    /// A positive shift multiplies by alpha^k and a negative shift divides by alpha^-k. Returns
+    /// `None` if the box is not a plain polynumber or if a negative shift would give a term a
+    /// negative exponent.
+    pub fn shift(self, k: i32) -> Option<Self> {
+        let amount = Natural::from(k.unsigned_abs());
+        let terms = self.degree_terms()?;
+        let shifted = terms
+            .into_iter()
+            .map(|(degree, coeff)| {
+                let degree = if k >= 0 {
+                    degree + &amount
+                } else {
+                    degree.checked_sub(&amount)?
+                };
+                Some((degree, coeff))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::from_degree_terms(shifted))
+    }
+
+    /// Divide by another polynumber with remainder
+    ///
+    /// The degree of the remainder is less than the degree of the divisor. Returns `None` if
+    /// either box is not a plain polynumber, if the divisor is zero or if the division is not
+    /// exact in the integers, i.e. a leading coefficient does not divide evenly.
+    pub fn div_rem(self, divisor: &Self) -> Option<(Self, Self)> {
+        let (quot, rem) = dense_div_rem(&self.to_dense()?, &divisor.to_dense()?)?;
+        Some((Self::from_dense(&quot), Self::from_dense(&rem)))
+    }
+
+    /// Return the greatest common divisor of two polynumbers over the integers
+    ///
+    /// The content and the primitive parts are handled separately so that all coefficients
+    /// stay integral, and the result has a positive leading coefficient. The gcd of coprime
+    /// polynumbers is the gcd of their contents. Returns `None` if either box is not a plain
+    /// polynumber.
+    pub fn poly_gcd(self, other: Self) -> Option<Self> {
+        let gcd = dense_gcd(&self.to_dense()?, &other.to_dense()?);
+        Some(Self::from_dense(&gcd))
+    }
+
+    /// Substitute a polynumber for alpha
+    ///
+    /// Each term `c * alpha^k` is replaced by `c * value^k` and the terms are recombined with
+    /// box addition and multiplication. Returns `None` if the box is not a plain polynumber or
+    /// a degree does not fit into a `u32`.
+    pub fn compose(self, value: BoxValue<PolynumBox>) -> Option<Self> {
+        let mut result = Self::from_dense(&[]);
+        for (degree, coeff) in self.degree_terms()? {
+            let power = value.clone().pow(u32::try_from(&degree).ok()?);
+            result = result + BoxValue::from(coeff) * power;
+        }
+        Some(result)
+    }
+
+    /// Evaluate the polynumber at an integer value of alpha
+    ///
+    /// Only the non-zero terms are visited, so sparse polynumbers of a high degree are cheap to
+    /// evaluate at zero, one and minus one. Returns `None` if the box is not a plain polynumber
+    /// or if a power of any other value would have an exponent beyond `u64`.
+    pub fn evaluate(&self, x: impl Into<Integer>) -> Option<Integer> {
+        let x = x.into();
+        let mut acc = Integer::from(0);
+        let mut previous: Option<Natural> = None;
+        for (degree, coeff) in self.degree_terms()?.into_iter().rev() {
+            if let Some(previous) = previous {
+                acc *= integer_power(&x, &(previous - &degree))?;
+            }
+            acc += coeff;
+            previous = Some(degree);
+        }
+        match previous {
+            Some(lowest) => Some(acc * integer_power(&x, &lowest)?),
+            None => Some(acc),
+        }
+    }
+
+    /// Evaluate the polynumber at an `i64` value of alpha with checked arithmetic
+    ///
+    /// This uses Horner's scheme like [`BoxValue::evaluate`] but stays within `i64`. Returns
+    /// `None` if the box is not a plain polynumber, if a coefficient does not fit into an `i64`
+    /// or if an intermediate result overflows.
+    pub fn horner_eval(&self, x: i64) -> Option<i64> {
+        // the gaps between consecutive degrees are skipped with a single power of `x`
+        let mut acc = 0_i64;
+        let mut previous: Option<Natural> = None;
+        for (degree, coeff) in self.degree_terms()?.into_iter().rev() {
+            if let Some(previous) = previous {
+                acc = acc.checked_mul(checked_power(x, &(previous - &degree))?)?;
+            }
+            acc = acc.checked_add(i64::try_from(&coeff).ok()?)?;
+            previous = Some(degree);
+        }
+        match previous {
+            Some(lowest) => acc.checked_mul(checked_power(x, &lowest)?),
+            None => Some(acc),
+        }
+    }
+
+    /// Compare the values of two polynumbers at the given value of alpha
+    ///
+    /// Both are evaluated with [`BoxValue::evaluate`], so unlike the structural order of boxes
+    /// this reflects the numeric magnitude. Returns `None` if either box is not a plain
+    /// polynumber.
+    pub fn compare_as_polynomials(&self, other: &Self, alpha: i64) -> Option<Ordering> {
+        Some(self.evaluate(alpha)?.cmp(&other.evaluate(alpha)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::cmp::Ordering;
+
+    use malachite::{Integer, Natural, base::num::arithmetic::traits::Pow};
+
+    use crate::{BoxValue, PolynumBox, polynum::PolyBounds};
+
+    /// Operation whose result is cross-checked by evaluation
+    enum Op {
+        Add,
+        Sub,
+        Mul,
+        Derivative,
+    }
+
+    /// Check the result of an operation by evaluating both sides at several integer points
+    ///
+    /// The right operand is ignored for the derivative.
+    fn check_via_evaluation(a: &BoxValue<PolynumBox>, b: &BoxValue<PolynumBox>, op: Op) {
+        let derivative = a.clone().derivative();
+        for x in -3..=3 {
+            let l = a.evaluate(x).unwrap();
+            let r = b.evaluate(x).unwrap();
+            match op {
+                Op::Add => assert_eq!((a.clone() + b.clone()).evaluate(x), Some(l + r)),
+                Op::Sub => assert_eq!(
+                    (a.clone() + BoxValue::from(-1) * b.clone()).evaluate(x),
+                    Some(l - r)
+                ),
+                Op::Mul => assert_eq!((a.clone() * b.clone()).evaluate(x), Some(l * r)),
+                Op::Derivative => {
+                    // evaluate the derivative of the dense coefficients independently
+                    let coeffs = a.to_dense().unwrap();
+                    let exp = coeffs
+                        .iter()
+                        .enumerate()
+                        .skip(1)
+                        .rev()
+                        .fold(Integer::from(0), |acc, (i, c)| {
+                            acc * Integer::from(x) + c * Integer::from(i)
+                        });
+                    assert_eq!(derivative.evaluate(x), Some(exp));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bounds() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(3) * alpha.clone()
+            + BoxValue::from(-5) * alpha.clone() * alpha.clone() * alpha.clone()
+            + BoxValue::from(2) * alpha.clone() * alpha.clone() * alpha.clone() * alpha.clone();
+        let exp = PolyBounds {
+            min_degree: Natural::from(1_u32),
+            max_degree: Natural::from(4_u32),
+            min_coeff: Integer::from(-5),
+            max_coeff: Integer::from(3),
+            term_count: 3,
+        };
+        assert_eq!(poly.bounds(), Some(exp));
+
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(zero.bounds(), None);
+    }
+
+    #[test]
+    fn test_finite_difference() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(1)
+            + BoxValue::from(3) * alpha.clone()
+            + BoxValue::from(6) * alpha.clone() * alpha.clone();
+
+        let first = poly.finite_difference().unwrap();
+        assert_eq!(first, BoxValue::from(2) + BoxValue::from(3) * alpha.clone());
+        assert_eq!(first.finite_difference(), Some(BoxValue::from(1).cast()));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let alpha = BoxValue::alpha();
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+
+        let num = alpha.clone().pow(2) + BoxValue::from(-1);
+        let den = alpha.clone() + BoxValue::from(-1);
+        let exp = alpha.clone() + BoxValue::from(1);
+        assert_eq!(num.div_rem(&den), Some((exp, zero.clone())));
+
+        let num = alpha.clone().pow(2) + BoxValue::from(3);
+        let (quot, rem) = num.div_rem(&den).unwrap();
+        assert_eq!(quot, alpha.clone() + BoxValue::from(1));
+        assert_eq!(rem, BoxValue::from(4).cast());
+
+        let den = BoxValue::from(2) * alpha.clone();
+        assert_eq!(alpha.clone().div_rem(&den), None);
+        assert_eq!(alpha.clone().div_rem(&zero), None);
+
+        let nested = BoxValue::alpha().wrap::<PolynumBox>(1_u32);
+        assert_eq!(nested.div_rem(&alpha), None);
+    }
+
+    #[test]
+    fn test_compose() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(1) + alpha.clone().pow(2);
+        let three = BoxValue::from(3).cast::<PolynumBox>();
+        assert_eq!(poly.clone().compose(three), Some(BoxValue::from(10).cast()));
+
+        // (alpha - 1)^2 + 1 = alpha^2 - 2 alpha + 2
+        let shifted = alpha.clone() + BoxValue::from(-1);
+        let exp = alpha.clone().pow(2) + BoxValue::from(-2) * alpha.clone() + BoxValue::from(2);
+        assert_eq!(poly.compose(shifted), Some(exp));
+
+        let nested = BoxValue::alpha().wrap::<PolynumBox>(1_u32);
+        assert_eq!(nested.compose(BoxValue::alpha()), None);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(1) + BoxValue::from(-2) * alpha.clone() * alpha.clone();
+        assert_eq!(poly.evaluate(3), Some(Integer::from(-17)));
+        assert_eq!(
+            BoxValue::zero().cast::<PolynumBox>().evaluate(5),
+            Some(Integer::from(0))
+        );
+
+        let other = alpha.clone() + BoxValue::from(-4);
+        let cubic = alpha.clone() * alpha.clone() * alpha.clone() + BoxValue::from(7);
+        for (a, b) in [(&poly, &other), (&other, &cubic), (&cubic, &poly)] {
+            check_via_evaluation(a, b, Op::Add);
+            check_via_evaluation(a, b, Op::Sub);
+            check_via_evaluation(a, b, Op::Mul);
+            check_via_evaluation(a, b, Op::Derivative);
+        }
+    }
+
+    #[test]
+    fn test_from_sequence() {
+        let poly = BoxValue::from_sequence(&[1, -2, 3]);
+        let exp = vec![Integer::from(1), Integer::from(-2), Integer::from(3)];
+        assert_eq!(poly.to_coefficients(), Some(exp));
+
+        let alpha = BoxValue::alpha();
+        assert_eq!(
+            poly,
+            BoxValue::from(1)
+                + BoxValue::from(-2) * alpha.clone()
+                + BoxValue::from(3) * alpha.clone() * alpha.clone()
+        );
+        assert_eq!(
+            BoxValue::from_sequence(&[0, 0]).to_coefficients(),
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn test_gf_terms() {
+        let poly =
+            BoxValue::from(1) + 2_u32 * BoxValue::alpha() + BoxValue::alpha() * BoxValue::alpha();
+        let exp = vec![
+            (Natural::from(0_u32), Natural::from(1_u32)),
+            (Natural::from(1_u32), Natural::from(2_u32)),
+            (Natural::from(2_u32), Natural::from(1_u32)),
+        ];
+        assert_eq!(poly.gf_terms(), Some(exp));
+
+        let poly = BoxValue::from(1) + BoxValue::anti_alpha();
+        assert_eq!(poly.gf_terms(), None);
+    }
+
+    #[test]
+    fn test_moment() {
+        let poly =
+            BoxValue::from(1) + 2_u32 * BoxValue::alpha() + BoxValue::alpha() * BoxValue::alpha();
+        assert_eq!(poly.moment(0), Some(1.0));
+        assert_eq!(poly.moment(1), Some(1.0));
+        assert_eq!(poly.moment(2), Some(1.5));
+
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(zero.moment(1), None);
+    }
+
+    #[test]
+    fn test_coefficient_entropy() {
+        let alpha = BoxValue::alpha();
+        let uniform = BoxValue::from(3)
+            + 3_u32 * alpha.clone()
+            + 3_u32 * alpha.clone() * alpha.clone()
+            + 3_u32 * alpha.clone() * alpha.clone() * alpha.clone();
+        assert_eq!(uniform.coefficient_entropy(), Some(2.0));
+
+        let skewed = BoxValue::from(1) + 3_u32 * alpha.clone() + alpha.clone() * alpha.clone();
+        assert!(skewed.coefficient_entropy().unwrap() < 3.0_f64.log2());
+
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(zero.coefficient_entropy(), Some(0.0));
+    }
+
+    #[test]
+    fn test_squarefree_part() {
+        let one = BoxValue::from(1);
+        let alpha = BoxValue::alpha();
+        let minus = alpha.clone() + BoxValue::from(-1);
+        let plus = alpha.clone() + one.clone();
+
+        let poly = minus.clone() * minus.clone() * plus.clone();
+        let exp = alpha.clone() * alpha.clone() + BoxValue::from(-1);
+        assert_eq!(poly.squarefree_part(), Some(exp));
+
+        let poly = minus.clone() * plus.clone();
+        assert_eq!(poly.squarefree_part(), Some(poly));
+    }
+
+    #[test]
+    fn test_root_multiplicity() {
+        let alpha = BoxValue::alpha();
+        let minus_two = alpha.clone() + BoxValue::from(-2);
+        let plus_one = alpha.clone() + BoxValue::from(1);
+
+        let poly = minus_two.clone() * minus_two.clone() * minus_two.clone() * plus_one;
+        assert_eq!(poly.root_multiplicity(2), Some(3));
+        assert_eq!(poly.root_multiplicity(-1), Some(1));
+        assert_eq!(poly.root_multiplicity(0), Some(0));
+
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(zero.root_multiplicity(0), None);
+    }
+
+    #[test]
+    fn test_leading_coefficient() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(6) + BoxValue::from(3) * alpha.clone().pow(2);
+        assert_eq!(poly.leading_coefficient(), Some(Integer::from(3)));
+        assert_eq!(poly.constant_term(), Some(Integer::from(6)));
+
+        let poly = alpha.clone() + BoxValue::from(-4) * alpha.clone().pow(3);
+        assert_eq!(poly.leading_coefficient(), Some(Integer::from(-4)));
+        assert_eq!(poly.constant_term(), Some(Integer::from(0)));
+
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(zero.leading_coefficient(), Some(Integer::from(0)));
+        assert_eq!(zero.constant_term(), Some(Integer::from(0)));
+        assert_eq!(alpha.into_anti().leading_coefficient(), None);
+    }
+
+    #[test]
+    fn test_poly_gcd() {
+        let alpha = BoxValue::alpha();
+        let minus = alpha.clone() + BoxValue::from(-1);
+        let plus = alpha.clone() + BoxValue::from(1);
+        let square = alpha.clone().pow(2) + BoxValue::from(-1);
+        assert_eq!(square.clone().poly_gcd(minus.clone()), Some(minus.clone()));
+
+        let left = BoxValue::from(2) * minus.clone() * plus.clone();
+        let right = BoxValue::from(6) * minus.clone() * minus.clone();
+        assert_eq!(
+            left.poly_gcd(right),
+            Some(BoxValue::from(2) * minus.clone())
+        );
+
+        let two = BoxValue::from(2).cast::<PolynumBox>();
+        let coprime = (BoxValue::from(2) * plus).poly_gcd(BoxValue::from(4) * minus.clone());
+        assert_eq!(coprime, Some(two));
+        assert_eq!(square.poly_gcd(minus.into_anti()), None);
+    }
+
+    #[test]
+    fn test_terms_by_degree() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(6) + BoxValue::from(3) * alpha.clone().pow(2);
+        let exp = vec![
+            (Natural::from(0_u32), Integer::from(6)),
+            (Natural::from(2_u32), Integer::from(3)),
+        ];
+        assert_eq!(poly.terms_by_degree(), Some(exp));
+
+        let poly = BoxValue::from(-1) * alpha.clone().pow(3) + alpha.clone();
+        let exp = vec![
+            (Natural::from(1_u32), Integer::from(1)),
+            (Natural::from(3_u32), Integer::from(-1)),
+        ];
+        assert_eq!(poly.terms_by_degree(), Some(exp));
+
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(zero.terms_by_degree(), Some(Vec::new()));
+        assert_eq!(alpha.into_anti().terms_by_degree(), None);
+    }
+
+    #[test]
+    fn test_horner_eval() {
+        let alpha = BoxValue::alpha();
+        let polys = vec![
+            BoxValue::from(6) + BoxValue::from(3) * alpha.clone().pow(2),
+            BoxValue::from(-1) * alpha.clone().pow(3) + alpha.clone() + BoxValue::from(-7),
+            BoxValue::from_sequence(&[2, 0, -5, 1, 4]),
+        ];
+        for poly in polys {
+            let coeffs = poly.to_dense().unwrap();
+            for x in [-4_i64, -1, 0, 1, 3, 10] {
+                let naive: i64 = coeffs
+                    .iter()
+                    .enumerate()
+                    .map(|(k, c)| i64::try_from(c).unwrap() * x.pow(k as u32))
+                    .sum();
+                assert_eq!(poly.horner_eval(x), Some(naive));
+            }
+        }
+
+        let poly = alpha.clone().pow(5);
+        assert_eq!(poly.horner_eval(1 << 13), None);
+        assert_eq!(poly.horner_eval(1 << 10), Some(1 << 50));
+        assert_eq!(alpha.clone().into_anti().horner_eval(1), None);
+
+        // sparse terms of a high degree are not expanded
+        let sparse = BoxValue::alpha_pow(1_000_000_000) + BoxValue::from(-3) * alpha;
+        assert_eq!(sparse.horner_eval(1), Some(-2));
+        assert_eq!(sparse.horner_eval(-1), Some(4));
+        assert_eq!(sparse.horner_eval(0), Some(0));
+        assert_eq!(sparse.horner_eval(2), None);
+        assert_eq!(
+            BoxValue::from(5).cast::<PolynumBox>().horner_eval(0),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_is_homogeneous() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(3) * alpha.clone().pow(2) + alpha.clone().pow(2);
+        assert_eq!(poly.is_homogeneous(), Some(Natural::from(2_u32)));
+
+        let poly = BoxValue::from(-2) * alpha.clone().pow(3) + alpha.clone().pow(3);
+        assert_eq!(poly.is_homogeneous(), Some(Natural::from(3_u32)));
+
+        let mixed = BoxValue::from(1) + alpha.clone();
+        assert_eq!(mixed.is_homogeneous(), None);
+
+        let constant = BoxValue::from(-7).cast::<PolynumBox>();
+        assert_eq!(constant.is_homogeneous(), Some(Natural::from(0_u32)));
+
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(zero.is_homogeneous(), None);
+        assert_eq!(alpha.into_anti().is_homogeneous(), None);
+    }
+
+    #[test]
+    fn test_truncate_degree() {
+        let alpha = BoxValue::alpha();
+        let poly = (BoxValue::from(1) + alpha.clone()).pow(4);
+        let exp = BoxValue::from(1) + BoxValue::from(4) * &alpha + BoxValue::from(6) * alpha.pow(2);
+        assert_eq!(poly.truncate_degree(2_u32), Some(exp));
+        assert_eq!(poly.truncate_degree(4_u32), Some(poly.clone()));
+        assert_eq!(
+            poly.truncate_degree(0_u32),
+            Some(BoxValue::from(1).cast::<PolynumBox>())
+        );
+
+        let prod = BoxValue::from_sequence(&[2, -1, 5]) * BoxValue::from_sequence(&[-3, 0, 0, 7]);
+        let truncated = prod.truncate_degree(3_u32).unwrap();
+        let exp = vec![
+            Integer::from(-6),
+            Integer::from(3),
+            Integer::from(-15),
+            Integer::from(14),
+        ];
+        assert_eq!(truncated.to_coefficients(), Some(exp));
+        assert_eq!(truncated.is_homogeneous(), None);
+
+        let sparse = BoxValue::alpha_pow(1_000_000_000) + BoxValue::from(3);
+        assert_eq!(
+            sparse.truncate_degree(5_u32),
+            Some(BoxValue::from(3).cast::<PolynumBox>())
+        );
+
+        assert_eq!(BoxValue::alpha().into_anti().truncate_degree(1_u32), None);
+    }
+
+    #[test]
+    fn test_from_coefficients() {
+        let alpha = BoxValue::alpha();
+        assert_eq!(
+            BoxValue::from_coefficients(&[6, 0, 3]),
+            BoxValue::from(6) + BoxValue::from(3) * alpha.clone().pow(2)
+        );
+        assert_eq!(
+            BoxValue::from_coefficients(&[0, 0, -2, 1]),
+            BoxValue::from(-2) * alpha.clone().pow(2) + alpha.clone().pow(3)
+        );
+        assert_eq!(
+            BoxValue::from_coefficients(&[]),
+            BoxValue::zero().cast::<PolynumBox>()
+        );
+
+        let poly = BoxValue::from(-4) + BoxValue::from(5) * alpha;
+        let coeffs: Vec<i64> = poly
+            .to_coefficients()
+            .unwrap()
+            .iter()
+            .map(|c| i64::try_from(c).unwrap())
+            .collect();
+        assert_eq!(BoxValue::from_coefficients(&coeffs), poly);
+    }
+
+    #[test]
+    fn test_compare_as_polynomials() {
+        // structurally the higher degree wins, numerically the large constant does
+        let high = BoxValue::alpha().pow(3);
+        let low = BoxValue::from(100) + BoxValue::alpha();
+        assert_eq!(high.cmp(&low), Ordering::Greater);
+        assert_eq!(high.compare_as_polynomials(&low, 2), Some(Ordering::Less));
+        assert_eq!(
+            high.compare_as_polynomials(&low, 10),
+            Some(Ordering::Greater)
+        );
+
+        let neg = BoxValue::from(-1) * BoxValue::alpha();
+        assert_eq!(
+            neg.compare_as_polynomials(&neg.clone(), 7),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            high.compare_as_polynomials(&BoxValue::alpha().into_anti(), 2),
+            None
+        );
+
+        // sparse terms of a high degree are not expanded
+        let sparse = BoxValue::alpha_pow(1_000_000_000)
+            + BoxValue::from(-1) * BoxValue::alpha_pow(999_999_999);
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(
+            sparse.compare_as_polynomials(&zero, 1),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            sparse.compare_as_polynomials(&zero, -1),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            sparse.compare_as_polynomials(&zero, 0),
+            Some(Ordering::Equal)
+        );
+        let gapped = BoxValue::alpha_pow(70) + BoxValue::from(-5) * BoxValue::alpha();
+        assert_eq!(
+            gapped.evaluate(3),
+            Some(Integer::from(3).pow(70_u64) - Integer::from(15))
+        );
+    }
+
+    #[test]
+    fn test_is_palindromic() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(1) + BoxValue::from(2) * &alpha + alpha.clone().pow(2);
+        assert_eq!(poly.is_palindromic(), Some(true));
+        assert_eq!(
+            BoxValue::from_coefficients(&[-3, 0, 5, 0, -3]).is_palindromic(),
+            Some(true)
+        );
+        assert_eq!(
+            BoxValue::from(4).cast::<PolynumBox>().is_palindromic(),
+            Some(true)
+        );
+
+        assert_eq!(
+            BoxValue::from_coefficients(&[1, 2, 3]).is_palindromic(),
+            Some(false)
+        );
+        assert_eq!(
+            BoxValue::from_coefficients(&[1, 0, -1]).is_palindromic(),
+            Some(false)
+        );
+        // the missing constant term counts as a zero coefficient
+        assert_eq!(
+            (&alpha + alpha.clone().pow(2)).is_palindromic(),
+            Some(false)
+        );
+
+        assert_eq!(BoxValue::zero().cast::<PolynumBox>().is_palindromic(), None);
+
+        // sparse terms of a high degree are not expanded
+        let high = BoxValue::alpha_pow(1_000_000_000);
+        assert_eq!(
+            (BoxValue::from(2) * &high + BoxValue::from(2)).is_palindromic(),
+            Some(true)
+        );
+        assert_eq!(
+            (BoxValue::from(2) * &high + BoxValue::from(1)).is_palindromic(),
+            Some(false)
+        );
+        assert_eq!(
+            (&high + BoxValue::alpha_pow(999_999_999)).is_palindromic(),
+            Some(false)
+        );
+        assert_eq!(alpha.into_anti().is_palindromic(), None);
+    }
+
+    #[test]
+    fn test_shift() {
+        let alpha = BoxValue::alpha();
+        let binom = BoxValue::from(1) + alpha.clone();
+        assert_eq!(
+            binom.clone().shift(2),
+            Some(alpha.clone().pow(2) + alpha.clone().pow(3))
+        );
+        assert_eq!(binom.clone().shift(0), Some(binom.clone()));
+
+        let poly = BoxValue::from(-4) * alpha.clone().pow(3) + alpha.clone().pow(5);
+        assert_eq!(
+            poly.clone().shift(-3),
+            Some(BoxValue::from(-4) + alpha.clone().pow(2))
+        );
+        assert_eq!(poly.shift(-4), None);
+        assert_eq!(binom.shift(-1), None);
+
+        let zero = BoxValue::zero().cast::<PolynumBox>();
+        assert_eq!(zero.clone().shift(-3), Some(zero.clone()));
+        assert_eq!(zero.clone().shift(3), Some(zero));
+        assert_eq!(alpha.into_anti().shift(1), None);
+
+        // the exponents are shifted without expanding the dense coefficients
+        let high = BoxValue::alpha_pow(1_000_000_000);
+        assert_eq!(high.shift(1), Some(BoxValue::alpha_pow(1_000_000_001)));
+        assert_eq!(
+            BoxValue::alpha().shift(i32::MAX),
+            Some(BoxValue::alpha_pow(1 << 31))
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Property tests of the algebraic laws of boxes
+
+use proptest::prelude::*;
+
+use crate::{AnyBox, BoxValue, Color};
+
+/// Generate nested boxes and anti-boxes of at most the given depth and breadth
+fn arb_box(depth: u32, breadth: usize) -> impl Strategy<Value = BoxValue<AnyBox>> {
+    let leaf = prop_oneof![Just(BoxValue::empty()), Just(BoxValue::anti_empty())];
+    leaf.prop_recursive(
+        depth,
+        breadth as u32 * depth,
+        breadth as u32,
+        move |inner| {
+            (
+                any::<bool>(),
+                prop::collection::vec((inner, 1_u32..4), 0..=breadth),
+            )
+                .prop_map(|(anti, children)| {
+                    let mut value = BoxValue::empty();
+                    for (child, mul) in children {
+                        value.extend_with_mul(child, mul);
+                    }
+                    if anti { value.into_anti() } else { value }
+                })
+        },
+    )
+}
+
+/// Generate boxes whose root is not an anti-box
+fn arb_black_box(depth: u32, breadth: usize) -> impl Strategy<Value = BoxValue<AnyBox>> {
+    arb_box(depth, breadth).prop_map(|mut value| {
+        value.set_color(0, Color::Black);
+        value
+    })
+}
+
+proptest! {
+    #[test]
+    fn prop_add_associative(
+        a in arb_black_box(3, 3),
+        b in arb_black_box(3, 3),
+        c in arb_black_box(3, 3),
+    ) {
+        let left = (a.clone() + b.clone()) + c.clone();
+        let right = a + (b + c);
+        prop_assert_eq!(left, right);
+    }
+
+    #[test]
+    fn prop_mul_commutative(a in arb_black_box(3, 3), b in arb_black_box(3, 3)) {
+        prop_assert_eq!(a.clone() * b.clone(), b * a);
+    }
+
+    #[test]
+    fn prop_mul_distributive(
+        a in arb_black_box(3, 3),
+        b in arb_black_box(3, 3),
+        c in arb_black_box(3, 3),
+    ) {
+        let left = a.clone() * (b.clone() + c.clone());
+        let right = a.clone() * b + a * c;
+        prop_assert!(left.reduces_to(&right));
+    }
+
+    #[test]
+    fn prop_annihilate_idempotent(x in arb_box(4, 3)) {
+        let once = x.annihilate();
+        prop_assert_eq!(once.clone().annihilate(), once);
+    }
+
+    #[test]
+    fn prop_canonical_idempotent(x in arb_box(4, 3)) {
+        let once = x.canonical();
+        prop_assert_eq!(once.clone().canonical(), once);
+    }
+}
@@ -0,0 +1,204 @@
+//! Signs of boxes and anti-boxes
+
+use crate::{BoxType, BoxValue, Color};
+
+impl<T: BoxType> BoxValue<T> {
+    /// Return the magnitude of the box
+    ///
+    /// Every anti-box is turned into a box, recursively at every level of nesting, while the
+    /// multiplicities are left intact. Children that become equal are merged afterwards.
+    pub fn abs(mut self) -> Self {
+        self.colors.fill(Color::Black);
+        self.annihilate()
+    }
+
+    /// Split the box into its positive and negative parts
+    ///
+    /// The positive part holds the child boxes and the negative part the child anti-boxes
+    /// turned into boxes, so that adding the anti-boxes of the negative part to the positive
+    /// part gives the original box back. Only the signs of the immediate children are split,
+    /// anti-boxes nested deeper remain part of the content of their child.
+    pub fn split_sign(self) -> (Self, Self) {
+        let mut positive = BoxValue::<T>::new();
+        positive.kinds.push(self.kinds[0]);
+        positive.colors.push(self.colors[0]);
+        positive.multiplicities.push(self.multiplicities[0].clone());
+        positive.lengths.push(1);
+        let mut negative = positive.clone();
+
+        for child in self {
+            if child.is_anti() {
+                negative.extend(child.into_anti());
+            } else {
+                positive.extend(child);
+            }
+        }
+        (positive.annihilate(), negative.annihilate())
+    }
+
+    /// Move the sign of an anti-box root into its immediate children
+    ///
+    /// An anti-box holds the same value as the box of its inverted children, so the canonical
+    /// position of a sign is the color of the immediate children and the root is always a
+    /// box. Anti-boxes nested deeper are left in place, since their colors are part of the
+    /// content of their parent, e.g. a red child of a polynumber is a negative coefficient
+    /// while a red child of that child would be a negative exponent. The children are sorted
+    /// again but not annihilated.
+    pub fn simplify_signs(mut self) -> Self {
+        if self.is_anti() {
+            self.colors[0] = Color::Black;
+            for idx in self.child_indices().collect::<Vec<_>>() {
+                self.colors[idx] = self.colors[idx].invert();
+            }
+            self.sort_immediate_children();
+        }
+        self
+    }
+
+    /// Return a copy of the box with the color of the root inverted
+    pub fn to_anti(&self) -> Self {
+        self.clone().into_anti()
+    }
+
+    /// Return a copy of the box whose root is a box
+    pub fn as_box(&self) -> Self {
+        let mut result = self.clone();
+        result.colors[0] = Color::Black;
+        result
+    }
+
+    /// Return a copy of the box whose root is an anti-box
+    pub fn as_anti(&self) -> Self {
+        let mut result = self.clone();
+        result.colors[0] = Color::Red;
+        result
+    }
+
+    /// Return the sign of the canonical form of the box as `1`, `-1` or `0`
+    ///
+    /// The sign is zero if the canonical form has no children, e.g. for the zero box or an
+    /// anti-box whose children cancel. Otherwise it is the color of the child box returned by
+    /// [`BoxValue::leading_term`], so `from(-5).sign() == -1`, and for polynumbers it is the
+    /// sign of the leading coefficient.
+    pub fn sign(&self) -> i32 {
+        match self.clone().canonical().leading_term() {
+            None => 0,
+            Some((leading, _)) if leading.is_anti() => -1,
+            Some(_) => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{AnyBox, BoxValue};
+
+    #[test]
+    fn test_simplify_signs() {
+        let inner = BoxValue::from(-3);
+        let outer = BoxValue::from(3).into_anti();
+        assert_ne!(inner, outer);
+        assert_eq!(outer.clone().simplify_signs(), inner);
+        assert_eq!(inner.clone().simplify_signs(), inner);
+
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(2) + BoxValue::from(-5) * &alpha;
+        let negated = BoxValue::from(-2) + BoxValue::from(5) * &alpha;
+        let simplified = poly.clone().into_anti().simplify_signs();
+        assert_eq!(simplified, negated);
+        assert_eq!(simplified.evaluate(3), poly.evaluate(3).map(|value| -value));
+    }
+
+    #[test]
+    fn test_abs() {
+        assert_eq!(BoxValue::from(-5).abs(), BoxValue::from(5));
+        assert_eq!(BoxValue::anti_zero().abs(), BoxValue::zero());
+
+        let alpha = BoxValue::alpha();
+        let mixed = BoxValue::from(-2)
+            + BoxValue::from(3) * alpha.clone()
+            + BoxValue::from(-4) * alpha.clone() * alpha.clone();
+        let exp = BoxValue::from(2)
+            + BoxValue::from(3) * alpha.clone()
+            + BoxValue::from(4) * alpha.clone() * alpha.clone();
+        assert_eq!(mixed.abs(), exp);
+
+        let deep = BoxValue::from(-3)
+            .wrap::<AnyBox>(2_u32)
+            .into_anti()
+            .wrap::<AnyBox>(1_u32)
+            .into_anti();
+        let exp = BoxValue::from(3)
+            .wrap::<AnyBox>(2_u32)
+            .wrap::<AnyBox>(1_u32);
+        assert_eq!(deep.abs(), exp);
+    }
+
+    #[test]
+    fn test_split_sign() {
+        let (positive, negative) = BoxValue::from(-5).split_sign();
+        assert_eq!(positive, BoxValue::from(0));
+        assert_eq!(negative, BoxValue::from(5));
+
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(-2)
+            + BoxValue::from(3) * alpha.clone()
+            + BoxValue::from(-4) * alpha.clone() * alpha.clone();
+        let (positive, negative) = poly.clone().split_sign();
+        assert_eq!(positive, BoxValue::from(3) * alpha.clone());
+        assert_eq!(positive + BoxValue::from(-1) * negative, poly);
+
+        let mut nested = BoxValue::empty();
+        nested.extend(BoxValue::from(-1).wrap::<AnyBox>(2_u32));
+        nested.extend(BoxValue::from(4).into_anti());
+        let (positive, negative) = nested.clone().split_sign();
+        let mut recombined = positive;
+        for child in negative {
+            recombined.extend(child.into_anti());
+        }
+        assert!(recombined.reduces_to(&nested));
+    }
+
+    #[test]
+    fn test_sign_copies() {
+        let values = [
+            BoxValue::from(3).cast::<AnyBox>(),
+            BoxValue::from(-3).into_anti().cast(),
+            BoxValue::anti_empty(),
+        ];
+        for value in values {
+            assert_eq!(value.to_anti(), value.clone().into_anti());
+            assert!(!value.as_box().is_anti());
+            assert!(value.as_anti().is_anti());
+            assert_eq!(value.as_box().as_anti(), value.as_anti());
+        }
+
+        let value = BoxValue::from(3);
+        assert_eq!(value.as_box(), value);
+        assert_eq!(value.as_anti(), value.to_anti());
+    }
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(BoxValue::from(5).sign(), 1);
+        assert_eq!(BoxValue::from(-5).sign(), -1);
+        assert_eq!(BoxValue::from(5).into_anti().sign(), -1);
+        assert_eq!(BoxValue::from(-5).into_anti().sign(), 1);
+        assert_eq!(BoxValue::zero().sign(), 0);
+        assert_eq!(BoxValue::anti_zero().sign(), 0);
+
+        let mut cancelling = BoxValue::anti_empty();
+        cancelling.extend(BoxValue::zero());
+        cancelling.extend(BoxValue::anti_zero());
+        assert_eq!(cancelling.sign(), 0);
+
+        let poly = BoxValue::from(3) + BoxValue::from(-2) * BoxValue::alpha();
+        assert_eq!(poly.sign(), -1);
+
+        let alpha = BoxValue::alpha();
+        let poly = alpha.clone().pow(2) + BoxValue::from(-1) * alpha.clone();
+        assert_eq!(poly.sign(), 1);
+        assert_eq!(poly.into_anti().sign(), -1);
+    }
+}
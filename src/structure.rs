@@ -0,0 +1,456 @@
+//! Structural traversals and metrics of boxes
+
+use malachite::Natural;
+
+use crate::{AnyBox, BoxKind, BoxType, BoxValue, Color, annihilate, builder::BoxBuilder};
+
+impl<T: BoxType> BoxValue<T> {
+    /// Fold the box structure bottom-up
+    ///
+    /// The closure is called once for every box with its kind, its color and the folded results
+    /// of its immediate children paired with their multiplicities. The children are folded
+    /// before their parent without recursion, so arbitrarily deep boxes can be folded.
+    pub fn fold<R>(&self, f: &mut impl FnMut(BoxKind, Color, Vec<(R, Natural)>) -> R) -> R {
+        // the results of the children of a row are on top of the stack in stored order
+        let mut results: Vec<R> = Vec::new();
+        for i in (0..self.kinds.len()).rev() {
+            let end = i + self.lengths[i] as usize;
+            let mut children = Vec::new();
+            let mut j = i + 1;
+            while j < end {
+                let result = results
+                    .pop()
+                    .expect("children are folded before their parent");
+                children.push((result, self.multiplicities[j].clone()));
+                j += self.lengths[j] as usize;
+            }
+            results.push(f(self.kinds[i], self.colors[i], children));
+        }
+        results.pop().expect("the root is folded last")
+    }
+
+    /// Return the nesting depth of the box, which is zero for a box without children
+    pub fn depth(&self) -> u32 {
+        self.fold(&mut |_, _, children| {
+            children
+                .into_iter()
+                .map(|(depth, _)| depth + 1)
+                .max()
+                .unwrap_or(0)
+        })
+    }
+
+    /// Return the number of immediate child boxes
+    ///
+    /// Alias of [`BoxValue::len`] to go with [`BoxValue::depth`] and [`BoxValue::max_breadth`].
+    /// Every child box is counted once regardless of its multiplicity.
+    pub fn width(&self) -> usize {
+        self.len()
+    }
+
+    /// Return the largest number of child boxes at a single level of nesting
+    ///
+    /// The levels are counted below the root, so a box without children has breadth zero. As
+    /// for [`BoxValue::width`], multiplicities are ignored.
+    pub fn max_breadth(&self) -> usize {
+        let mut breadths: Vec<usize> = Vec::new();
+        // the ends of the rows enclosing the current row
+        let mut ends: Vec<usize> = Vec::new();
+        for i in 0..self.kinds.len() {
+            while ends.last().is_some_and(|&end| end <= i) {
+                ends.pop();
+            }
+            if !ends.is_empty() {
+                let level = ends.len() - 1;
+                if breadths.len() <= level {
+                    breadths.push(0);
+                }
+                breadths[level] += 1;
+            }
+            ends.push(i + self.lengths[i] as usize);
+        }
+        breadths.into_iter().max().unwrap_or(0)
+    }
+
+    /// Return the number of distinct child boxes at every level of nesting
+    ///
+    /// Unlike [`BoxValue::count_boxes`], multiplicities are ignored. Children of the same box
+    /// that are equal including their color are counted once and only one of them is
+    /// descended into, so for results of box arithmetic this is the number of rows below the
+    /// root.
+    pub fn count_terms_recursive(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![0];
+        while let Some(row) = stack.pop() {
+            let end = row + self.lengths[row] as usize;
+            let mut distinct: Vec<usize> = Vec::new();
+            let mut child = row + 1;
+            while child < end {
+                let duplicate = distinct.iter().any(|&other| {
+                    self.colors[other] == self.colors[child] && self.is_eq_content_at(other, child)
+                });
+                if !duplicate {
+                    distinct.push(child);
+                }
+                child += self.lengths[child] as usize;
+            }
+            count += distinct.len();
+            stack.extend(distinct);
+        }
+        count
+    }
+
+    /// Apply a function to the multiplicities of all child boxes at every level of nesting
+    ///
+    /// The multiplicity of the root is left untouched. Child boxes whose multiplicity maps to
+    /// zero are removed together with their content, and the result is annihilated afterwards
+    /// so that equal children are merged again.
+    pub fn map_multiplicities(self, f: &impl Fn(&Natural) -> Natural) -> Self {
+        let root_mul = self.get_multiplicity(0);
+        let mut result = self.fold(&mut |kind, color, children| {
+            let mut node = BoxValue::<AnyBox>::new();
+            node.kinds.push(kind);
+            node.colors.push(color);
+            node.multiplicities.push(Natural::from(1_u32));
+            node.lengths.push(1);
+            for (child, mul) in children {
+                let mul = f(&mul);
+                if mul != 0 {
+                    node.extend_with_mul(child, mul);
+                }
+            }
+            node
+        });
+        result.set_multiplicity(0, root_mul);
+        result.annihilate().cast()
+    }
+
+    /// Apply a function to every immediate child box and merge the results again
+    ///
+    /// The function receives each child as a box with multiplicity one. The result keeps the
+    /// multiplicity of the child and becomes an anti-box if the child was one, so children that
+    /// map to the same box are merged and cancel against anti-boxes just as in box addition.
+    /// The color and multiplicity of the root are preserved.
+    pub fn map_boxes(self, f: &impl Fn(BoxValue<AnyBox>) -> BoxValue<AnyBox>) -> Self {
+        let kind = self.kinds[0];
+        let root_mul = self.get_multiplicity(0);
+        let builder = match self.colors[0] {
+            Color::Black => BoxBuilder::<T>::new(),
+            Color::Red => BoxBuilder::<T>::new_anti(),
+        };
+
+        let mut result = self
+            .into_terms()
+            .fold(builder, |builder, (term, mul)| {
+                let color = term.get_color(0);
+                let mut mapped = f(term.as_box());
+                mapped.colors[0] = mapped.colors[0] + color;
+                builder.add_term(mapped, mul)
+            })
+            .build();
+
+        if !annihilate::is_number_kind(kind) {
+            result.kinds[0] = kind;
+        }
+        result.set_multiplicity(0, root_mul);
+        result
+    }
+
+    /// Replace every child box equal to `old` at every level of nesting by `new`
+    ///
+    /// A child matches if its content equals the content of `old`, so the multiplicity of the
+    /// child is kept, and an anti-box child is replaced by the anti-box of `new`. Children
+    /// nested inside other children are replaced as well, and the children are matched before
+    /// anything nested inside them is substituted. The root itself is never replaced. Children
+    /// that become equal are merged, and children that become an equal box and anti-box cancel.
+    pub fn substitute_term<U: BoxType, V: BoxType>(
+        self,
+        old: &BoxValue<U>,
+        new: BoxValue<V>,
+    ) -> Self {
+        let old = old.clone().cast::<AnyBox>();
+        let new = new.cast::<AnyBox>();
+        let root_mul = self.get_multiplicity(0);
+
+        // every box is folded into its original and its substituted form
+        type Folded = (BoxValue<AnyBox>, BoxValue<AnyBox>);
+        let (_, mut result) = self.fold(&mut |kind, color, children: Vec<(Folded, Natural)>| {
+            let mut original = BoxValue::<AnyBox>::new();
+            original.kinds.push(kind);
+            original.colors.push(color);
+            original.multiplicities.push(Natural::from(1_u32));
+            original.lengths.push(1);
+
+            let mut builder = match color {
+                Color::Black => BoxBuilder::<AnyBox>::new(),
+                Color::Red => BoxBuilder::<AnyBox>::new_anti(),
+            };
+            for ((child, substituted), mul) in children {
+                let term = if child.is_eq_content(&old) {
+                    let mut term = new.clone();
+                    term.colors[0] = term.colors[0] + child.get_color(0);
+                    term
+                } else {
+                    substituted
+                };
+                builder = builder.add_term(term, mul.clone());
+                original.extend_with_mul(child, mul);
+            }
+
+            let mut substituted = builder.build();
+            if !annihilate::is_number_kind(kind) {
+                substituted.kinds[0] = kind;
+            }
+            (original, substituted)
+        });
+        result.set_multiplicity(0, root_mul);
+        result.cast()
+    }
+
+    /// Merge equal child boxes at every level of nesting by adding their multiplicities
+    ///
+    /// Unlike [`BoxValue::annihilate`], boxes and equal anti-boxes are kept apart, so only
+    /// children that are equal including their color are merged. The children are sorted at
+    /// every level afterwards.
+    pub fn merge_like_terms(self) -> Self {
+        let root_mul = self.get_multiplicity(0);
+        let mut result = self.fold(
+            &mut |kind, color, mut children: Vec<(BoxValue<AnyBox>, _)>| {
+                children.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+                let mut node = BoxValue::<AnyBox>::new();
+                node.kinds.push(kind);
+                node.colors.push(color);
+                node.multiplicities.push(Natural::from(1_u32));
+                node.lengths.push(1);
+
+                let mut merged: Vec<(BoxValue<AnyBox>, Natural)> = Vec::new();
+                for (child, mul) in children {
+                    match merged.last_mut() {
+                        Some((last, last_mul)) if *last == child => *last_mul += mul,
+                        _ => merged.push((child, mul)),
+                    }
+                }
+                for (child, mul) in merged {
+                    node.extend_with_mul(child, mul);
+                }
+                node
+            },
+        );
+        result.set_multiplicity(0, root_mul);
+        result.cast()
+    }
+
+    /// Return the number of boxes that are not anti-boxes, including the root
+    ///
+    /// Every child is counted as often as its multiplicity, recursively, while the root is
+    /// counted once.
+    pub fn count_boxes(&self) -> Natural {
+        self.count_color(Color::Black)
+    }
+
+    /// Return the number of anti-boxes, including the root
+    ///
+    /// Every child is counted as often as its multiplicity, recursively, while the root is
+    /// counted once.
+    pub fn count_anti_boxes(&self) -> Natural {
+        self.count_color(Color::Red)
+    }
+
+    fn count_color(&self, target: Color) -> Natural {
+        self.fold(&mut |_, color, children| {
+            let own = Natural::from(u32::from(color == target));
+            children
+                .into_iter()
+                .fold(own, |acc, (count, mul)| acc + count * mul)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use malachite::Natural;
+
+    use crate::{AnyBox, BoxKind, BoxValue, Color};
+
+    #[test]
+    fn test_fold() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(-2) + BoxValue::from(3) * alpha.clone() * alpha.clone();
+
+        // number of anti-boxes weighted by multiplicity
+        let anti_count = poly.fold(&mut |_, color, children| {
+            let own = Natural::from(u32::from(color == Color::Red));
+            children
+                .into_iter()
+                .fold(own, |acc, (count, mul)| acc + count * mul)
+        });
+        assert_eq!(anti_count, 2_u32);
+
+        assert_eq!(BoxValue::empty().depth(), 0);
+        assert_eq!(BoxValue::from(5).depth(), 1);
+        assert_eq!(poly.depth(), 2);
+
+        let deep = BoxValue::from(1)
+            .wrap::<AnyBox>(1_u32)
+            .wrap::<AnyBox>(2_u32);
+        assert_eq!(deep.depth(), 3);
+    }
+
+    #[test]
+    fn test_map_multiplicities() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(1) + 3_u32 * alpha.clone().pow(2);
+
+        // the exponents are multiplicities as well
+        let doubled = poly
+            .clone()
+            .map_multiplicities(&|m| m * Natural::from(2_u32));
+        assert_eq!(doubled, BoxValue::from(2) + 6_u32 * alpha.clone().pow(4));
+
+        // 2 alpha vanishes, 3 alpha^2 loses its exponent and merges with the constant
+        let poly = poly + 2_u32 * alpha.clone();
+        let reduced = poly.map_multiplicities(&|m| m % Natural::from(2_u32));
+        assert_eq!(reduced, BoxValue::from(2).cast());
+
+        let unchanged = BoxValue::from(-4).map_multiplicities(&|m| m.clone());
+        assert_eq!(unchanged, BoxValue::from(-4));
+    }
+
+    #[test]
+    fn test_map_boxes() {
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(1) + BoxValue::from(-2) * &alpha + 3_u32 * alpha.clone().pow(2);
+
+        // raising every exponent by one multiplies by alpha
+        let shifted = poly
+            .clone()
+            .map_boxes(&|term| term + BoxValue::from(1).cast());
+        assert_eq!(shifted, poly.clone() * &alpha);
+
+        // all terms collapse into the constant term, -2 cancels against 1 + 3
+        let collapsed = poly.map_boxes(&|_| BoxValue::zero().cast());
+        assert_eq!(collapsed, BoxValue::from(2).cast());
+
+        let anti = BoxValue::from(3).into_anti().map_boxes(&|term| term);
+        assert_eq!(anti, BoxValue::from(3).into_anti());
+    }
+
+    #[test]
+    fn test_substitute_term() {
+        let alpha = BoxValue::alpha();
+        let alpha_term = BoxValue::from(1);
+        let alpha_sq_term = BoxValue::from(2);
+
+        let poly = BoxValue::from(3) + BoxValue::from(-2) * &alpha;
+        let exp = BoxValue::from(3) + BoxValue::from(-2) * alpha.clone().pow(2);
+        assert_eq!(
+            poly.substitute_term(&alpha_term, alpha_sq_term.clone()),
+            exp
+        );
+
+        // the new term collides with the existing alpha and both merge
+        let poly = 2_u32 * alpha.clone() + 5_u32 * alpha.clone().pow(2);
+        assert_eq!(
+            poly.substitute_term(&alpha_sq_term, alpha_term.clone()),
+            7_u32 * alpha.clone()
+        );
+
+        // occurrences nested inside other children are replaced too
+        let mut inner = BoxValue::empty();
+        inner.extend(alpha_term.clone());
+        let mut outer = BoxValue::empty();
+        outer.extend_with_mul(alpha_term.clone(), 2_u32);
+        outer.extend(inner);
+
+        let mut exp_inner = BoxValue::empty();
+        exp_inner.extend(BoxValue::from(4));
+        let mut exp = BoxValue::empty();
+        exp.extend_with_mul(BoxValue::from(4), 2_u32);
+        exp.extend(exp_inner);
+        exp.sort_immediate_children();
+        assert_eq!(outer.substitute_term(&alpha_term, BoxValue::from(4)), exp);
+    }
+
+    #[test]
+    fn test_count_terms_recursive() {
+        assert_eq!(BoxValue::zero().count_terms_recursive(), 0);
+        assert_eq!(BoxValue::from(1000).count_terms_recursive(), 1);
+
+        // 1000 + 500 alpha + alpha^7: three terms and the empty boxes below the two powers
+        let alpha = BoxValue::alpha();
+        let poly = BoxValue::from(1000) + 500_u32 * alpha.clone() + alpha.clone().pow(7);
+        assert_eq!(poly.count_terms_recursive(), 5);
+
+        // the duplicate child and everything below it is counted once
+        let mut nested = BoxValue::empty();
+        nested.extend_with_mul(poly.clone(), 3_u32);
+        nested.extend(poly.clone());
+        nested.extend(poly.into_anti());
+        assert_eq!(nested.count_terms_recursive(), 2 + 2 * 5);
+    }
+
+    #[test]
+    fn test_count_boxes() {
+        let anti = BoxValue::anti_empty();
+        assert_eq!(anti.count_boxes(), 0_u32);
+        assert_eq!(anti.count_anti_boxes(), 1_u32);
+
+        let poly = BoxValue::from(-2) + BoxValue::from(3) * BoxValue::alpha();
+        assert_eq!(poly.count_boxes(), 7_u32);
+        assert_eq!(poly.count_anti_boxes(), 2_u32);
+
+        let mut mixed = BoxValue::from(-2).wrap::<AnyBox>(3_u32).into_anti();
+        mixed.extend_with_mul(BoxValue::anti_zero(), 4_u32);
+        assert_eq!(mixed.count_boxes(), 3_u32);
+        assert_eq!(mixed.count_anti_boxes(), 11_u32);
+    }
+
+    #[test]
+    fn test_breadth() {
+        let poly = BoxValue::from(1) + BoxValue::alpha() + 3_u32 * BoxValue::alpha().pow(2);
+        assert_eq!(poly.width(), 3);
+        assert_eq!(poly.max_breadth(), 3);
+        assert_eq!(BoxValue::empty().max_breadth(), 0);
+
+        let mut chain = BoxValue::empty();
+        for _ in 0..10 {
+            chain = chain.wrap::<AnyBox>(2_u32);
+        }
+        assert_eq!(chain.width(), 1);
+        assert_eq!(chain.max_breadth(), 1);
+
+        let mut mixed = BoxValue::empty();
+        mixed.extend(poly.clone());
+        mixed.extend(poly * BoxValue::alpha());
+        assert_eq!(mixed.width(), 2);
+        assert_eq!(mixed.max_breadth(), 6);
+    }
+
+    #[test]
+    fn test_merge_like_terms() {
+        let mut built = BoxValue::from(0).cast::<AnyBox>();
+        built.set_kind(0, BoxKind::Num);
+        built.extend_with_mul(BoxValue::zero(), 2_u32);
+        built.extend_with_mul(BoxValue::zero(), 3_u32);
+        let five = built.merge_like_terms();
+        assert_eq!(five, BoxValue::from(5).cast());
+
+        // three built as a sum and as a plain number box are equal after merging
+        let mut sum = BoxValue::from(0).cast::<AnyBox>();
+        sum.set_kind(0, BoxKind::Num);
+        sum.extend_with_mul(BoxValue::zero(), 1_u32);
+        sum.extend_with_mul(BoxValue::zero(), 2_u32);
+        let mut outer = BoxValue::empty();
+        outer.extend_with_mul(sum, 4_u32);
+        outer.extend_with_mul(BoxValue::from(3), 5_u32);
+        outer.extend(BoxValue::from(3).into_anti());
+
+        let mut exp = BoxValue::empty();
+        exp.extend_with_mul(BoxValue::from(3), 9_u32);
+        exp.extend(BoxValue::from(3).into_anti());
+        exp.sort_immediate_children();
+        assert_eq!(outer.merge_like_terms(), exp);
+    }
+}